@@ -1,12 +1,18 @@
 use ecies::encrypt;
 use interact::site::SITE_LINK;
-use mechanics::board::PositionVectors;
+use mechanics::board::{CompactBoard, PositionVectors};
+use mechanics::game::{GamePhase, TurnState};
+use mechanics::position::FireOutcome;
 use mechanics::position::FirePosition;
 use mechanics::position::FiredState;
-use mechanics::ship::Ship;
+use mechanics::position::Target;
+use mechanics::rules::GameRules;
+use mechanics::ship::FleetPlacement;
+use mechanics::ship::{Ship, ShipKind};
 use utils_files::event_source_state::EventSourceState;
 use utils_files::request::fire_on_position;
 use utils_files::request::get_request;
+use utils_files::request::submit_fleet_placement;
 use utils_files::web_error::ClientError;
 use wasm_bindgen::JsValue;
 use web_sys::Window;
@@ -22,15 +28,21 @@ pub struct Board {
     player_titles: Option<Vec<String>>,
     player_index: Option<usize>,
     challenge: Option<String>,
-    round: u32,
+    phase: GamePhase,
+    turn: TurnState,
+    rules: GameRules,
+    placed: bool,
     event_source: EventSourceState,
 }
 
 pub enum BoardMsg {
     AwaitUpdate,
-    Update((String, String, String, String)),
+    Update((String, String, String, String, String, String, String)),
     EndUpdate,
     Fire(usize, usize, usize),
+    Randomize,
+    Place,
+    Placed(bool),
     Response(ClientError),
 }
 
@@ -78,7 +90,10 @@ impl Component for Board {
             player_titles: None,
             player_index: None,
             challenge: None,
-            round: 0,
+            phase: GamePhase::Lobby,
+            turn: TurnState::default(),
+            rules: GameRules::default(),
+            placed: false,
             event_source,
         }
     }
@@ -95,20 +110,23 @@ impl Component for Board {
                 }
                 self.send_update_request(ctx);
             }
-            Self::Message::Update((challenge, player_titles, ship_positions, game_state)) => {
+            Self::Message::Update((
+                challenge,
+                player_titles,
+                ship_positions,
+                game_state,
+                phase,
+                turn,
+                rules,
+            )) => {
                 self.challenge = if challenge.is_empty() {
                     None
                 } else {
-                    if !self
-                        .challenge
-                        .clone()
-                        .unwrap_or("".to_string())
-                        .eq(&challenge)
-                    {
-                        self.round += 1;
-                    }
                     Some(challenge.to_string())
                 };
+                self.phase = serde_json::from_str::<GamePhase>(&phase).unwrap_or_default();
+                self.turn = serde_json::from_str::<TurnState>(&turn).unwrap_or_default();
+                self.rules = serde_json::from_str::<GameRules>(&rules).unwrap_or_default();
                 self.player_titles = if player_titles.is_empty() {
                     self.player_index = None;
                     None
@@ -131,7 +149,11 @@ impl Component for Board {
                 self.board = if game_state.is_empty() {
                     None
                 } else {
-                    Some(serde_json::from_str::<PositionVectors>(&game_state).unwrap())
+                    Some(
+                        serde_json::from_str::<CompactBoard>(&game_state)
+                            .unwrap()
+                            .to_positions(),
+                    )
                 };
                 if ctx.props().log {
                     ctx.link()
@@ -145,6 +167,9 @@ impl Component for Board {
                 self.event_source.close_connection();
             }
             Self::Message::Fire(x_pos, y_pos, to) => {
+                if !matches!(self.phase, GamePhase::InProgress { .. }) || self.challenge.is_none() {
+                    return false;
+                }
                 let message_bytes: Vec<u8> = encrypt(
                     &serde_json::from_str::<Vec<u8>>(&ctx.props().player_id_key.clone()).unwrap(),
                     &self.challenge.clone().unwrap().as_bytes(),
@@ -153,15 +178,26 @@ impl Component for Board {
                 let player_index: usize = self.player_index.clone().unwrap();
                 let game_number: u32 = ctx.props().game_number;
                 ctx.link().send_future(async move {
-                    match fire_on_position::<FirePosition>(
-                        FirePosition::new(message_bytes, player_index, to, x_pos, y_pos),
+                    match fire_on_position::<FirePosition, Vec<FireOutcome>>(
+                        FirePosition::new(
+                            message_bytes,
+                            player_index,
+                            vec![Target::new(to, x_pos, y_pos)],
+                        ),
                         game_number,
                     )
                     .await
                     {
-                        Ok(_) => Self::Message::Response(ClientError::from(
+                        Ok(outcomes) => Self::Message::Response(ClientError::from(
                             file!(),
-                            "update(): fire request sent",
+                            &format!(
+                                "update(): fire request landed: {}",
+                                outcomes
+                                    .iter()
+                                    .map(|outcome: &FireOutcome| outcome.to_string())
+                                    .collect::<Vec<String>>()
+                                    .join(", ")
+                            ),
                         )),
                         Err(error) => Self::Message::Response(ClientError::from(
                             file!(),
@@ -170,6 +206,80 @@ impl Component for Board {
                     }
                 });
             }
+            Self::Message::Randomize => {
+                let rules: GameRules = GameRules {
+                    width: self.board.as_ref().map(|board| board.len()).unwrap_or(0),
+                    height: self
+                        .board
+                        .as_ref()
+                        .and_then(|board| board.first())
+                        .map(|column| column.len())
+                        .unwrap_or(0),
+                    fleet: self
+                        .ships
+                        .clone()
+                        .unwrap_or(Vec::new())
+                        .iter()
+                        .map(|ship: &Ship| (ship.kind, ship.location.len()))
+                        .collect::<Vec<(ShipKind, usize)>>(),
+                    ..self.rules.clone()
+                };
+                let ships: Vec<Ship> =
+                    match Ship::random_ships(&mut rand::thread_rng(), &rules, rules.no_touching) {
+                        Ok(ships) => ships,
+                        Err(error) => {
+                            ctx.link()
+                                .send_message(Self::Message::Response(ClientError::from(
+                                    file!(),
+                                    &format!("update(): could not randomize fleet: {}", error),
+                                )));
+                            return false;
+                        }
+                    };
+                let player_index: usize = self.player_index.unwrap_or(0);
+                if let Some(board) = self.board.as_mut() {
+                    for position in board.iter_mut().flatten() {
+                        if let Some(FiredState::Ship(_)) = position.fired_state.get(player_index) {
+                            position.fired_state[player_index] = FiredState::Untouched;
+                        }
+                    }
+                    for ship in ships.iter() {
+                        for (x_pos, y_pos) in ship.location.iter() {
+                            board[*x_pos][*y_pos].fired_state[player_index] =
+                                FiredState::Ship(ship.kind);
+                        }
+                    }
+                }
+                self.ships = Some(ships);
+            }
+            Self::Message::Place => {
+                let fleet_placement: FleetPlacement = FleetPlacement::new(
+                    ctx.props().access_key.clone(),
+                    self.player_index.unwrap_or(0),
+                    self.ships.clone().unwrap_or(Vec::new()),
+                );
+                let game_number: u32 = ctx.props().game_number;
+                ctx.link().send_future(async move {
+                    match submit_fleet_placement::<FleetPlacement>(fleet_placement, game_number)
+                        .await
+                    {
+                        Ok(accepted) => Self::Message::Placed(accepted),
+                        Err(error) => Self::Message::Response(
+                            error.push(file!(), "update(): could not send placement request"),
+                        ),
+                    }
+                });
+            }
+            Self::Message::Placed(accepted) => {
+                self.placed = accepted;
+                if !accepted {
+                    ctx.link()
+                        .send_message(Self::Message::Response(ClientError::from(
+                            file!(),
+                            "update(): fleet placement was rejected",
+                        )));
+                }
+            }
             Self::Message::Response(error) => {
                 web_sys::console::log_1(&JsValue::from(format!("{}", error)));
             }
@@ -178,9 +288,16 @@ impl Component for Board {
     }
 
     fn view(&self, _ctx: &Context<Self>) -> Html {
-        let indecies: Vec<(usize, usize)> = (0..10)
+        let (board_width, board_height): (usize, usize) = match &self.board {
+            Some(board) => (
+                board.len(),
+                board.first().map(|column| column.len()).unwrap_or(0),
+            ),
+            None => (0, 0),
+        };
+        let indecies: Vec<(usize, usize)> = (0..board_width)
             .map(|index| {
-                (0..10)
+                (0..board_height)
                     .map(|jndex| (index, jndex))
                     .collect::<Vec<(usize, usize)>>()
             })
@@ -192,12 +309,15 @@ impl Component for Board {
             _ctx.link()
                 .callback(move |_| Self::Message::Fire(index, jndex, to))
         };
-        let map_button_class = |result: &str, index: usize, jndex: usize| {
-            classes!(
-                "main_button",
-                format!("main_button_{}", result),
-                format!("button_row_{}", 9 - jndex),
-                format!("button_col_{}", index)
+        let map_button_class =
+            |result: &str| classes!("main_button", format!("main_button_{}", result));
+        let map_button_style = |index: usize, jndex: usize| {
+            format!(
+                "grid-row: {}; grid-column: {}; width: calc(var(--board_width) / {}); height: calc(var(--board_height) / {});",
+                board_height - jndex,
+                index + 1,
+                board_width,
+                board_height
             )
         };
         let number_of_players: usize = match &self.board.clone() {
@@ -209,8 +329,34 @@ impl Component for Board {
         html! {
             <div id={"Board_Component"}>
                 <div id={"Round_Heading"}>
-                    if self.round != 0 {
-                        <h2 class={classes!("round_title", "font")}>{ format!("Round {}", self.round) }</h2>
+                    if let GamePhase::InProgress { round } = self.phase {
+                        <h2 class={classes!("round_title", "font")}>{ format!("Round {}", round) }</h2>
+                        if let Some(on_move) = self.turn.on_move {
+                            <h3 class={classes!("turn_title", "font")}>{
+                                if self.player_index == Some(on_move) {
+                                    "Your Turn".to_string()
+                                } else {
+                                    format!("{}'s Turn", player_titles_unwrapped.get(on_move).cloned().unwrap_or_default())
+                                }
+                            }</h3>
+                        }
+                    } else if let GamePhase::Finished { winners } = &self.phase {
+                        <h2 class={classes!("round_title", "font")}>{
+                            format!(
+                                "Game Over: {} Won",
+                                winners
+                                    .iter()
+                                    .filter_map(|index: &usize| player_titles_unwrapped.get(*index).cloned())
+                                    .collect::<Vec<String>>()
+                                    .join(" & ")
+                            )
+                        }</h2>
+                    } else if self.phase == GamePhase::Placement && self.ships.is_some() {
+                        <h2 class={classes!("round_title", "font")}>{ "Place Your Fleet" }</h2>
+                        if !self.placed {
+                            <button class={classes!("placement_button", "font")} onclick={_ctx.link().callback(|_| Self::Message::Randomize)}>{ "Randomize" }</button>
+                            <button class={classes!("placement_button", "placement_button_ready", "font")} onclick={_ctx.link().callback(|_| Self::Message::Place)}>{ "Ready" }</button>
+                        }
                     } else {
                         <h2 class={classes!("round_title", "font")}>{ "Game Not Yet Started" }</h2>
                     }
@@ -220,7 +366,7 @@ impl Component for Board {
                         .into_iter()
                         .map(|index: usize| html! {
                             <div id={format!("{}", player_titles_unwrapped[index])}>
-                                <div class={"board"}>{
+                                <div class={"board"} style={format!("grid-template-columns: repeat({}, auto);", board_width)}>{
                                     if let Some(board) = self.board.clone() {
                                         indecies.clone()
                                             .into_iter()
@@ -229,31 +375,40 @@ impl Component for Board {
                                                     FiredState::Untouched => {
                                                         if index == player_index_unwrapped {
                                                             return html! {
-                                                                <button class={map_button_class("empty", x_pos, y_pos)} />
+                                                                <button class={map_button_class("empty")} style={map_button_style(x_pos, y_pos)} />
                                                             };
                                                         }
                                                         html! {
-                                                            <button class={map_button_class("untouched", x_pos, y_pos)} onclick={onclick(x_pos, y_pos, index)} />
+                                                            <button class={map_button_class("untouched")} style={map_button_style(x_pos, y_pos)} onclick={onclick(x_pos, y_pos, index)} />
                                                         }
                                                     }
                                                     FiredState::Miss => {
                                                         html! {
-                                                            <button class={map_button_class("miss", x_pos, y_pos)} />
+                                                            <button class={map_button_class("miss")} style={map_button_style(x_pos, y_pos)} />
                                                         }
                                                     }
                                                     FiredState::Hit => {
                                                         html! {
-                                                            <button class={map_button_class("hit", x_pos, y_pos)} />
+                                                            <button class={map_button_class("hit")} style={map_button_style(x_pos, y_pos)} />
                                                         }
                                                     }
                                                     FiredState::Empty => {
                                                         html! {
-                                                            <button class={map_button_class("empty", x_pos, y_pos)} />
+                                                            <button class={map_button_class("empty")} style={map_button_style(x_pos, y_pos)} />
+                                                        }
+                                                    }
+                                                    FiredState::Sunk(ship_kind) => {
+                                                        let ship_type: &str = ship_kind.asset_key();
+                                                        html! {
+                                                            <button class={classes!(map_button_class("sunk"), ship_type)} style={map_button_style(x_pos, y_pos)}>
+                                                                <img src={format!("http://127.0.0.1:8000/extra_files/ships_{ship_type}_day.svg")} />
+                                                            </button>
                                                         }
                                                     }
-                                                    FiredState::Ship(ship_type) => {
+                                                    FiredState::Ship(ship_kind) => {
+                                                        let ship_type: &str = ship_kind.asset_key();
                                                         html! {
-                                                            <button class={classes!(map_button_class("ship", x_pos, y_pos), ship_type)}>
+                                                            <button class={classes!(map_button_class("ship"), ship_type)} style={map_button_style(x_pos, y_pos)}>
                                                                 <img src={format!("http://127.0.0.1:8000/extra_files/ships_{ship_type}_day.svg")} />
                                                             </button>
                                                         }
@@ -306,7 +461,7 @@ impl Board {
         let player_id: String = _ctx.props().player_id_tag.clone();
         let access_message: String = _ctx.props().access_key.clone();
         _ctx.link().send_future(async move {
-            match get_request::<(String, String, String, String)>(
+            match get_request::<(String, String, String, String, String, String, String)>(
                 format!(
                     "{SITE_LINK}/game/{}/{}/{}",
                     game_number, player_id, access_message
@@ -3,7 +3,7 @@ use interact::link::GameListEntry;
 use interact::site::SITE_LINK;
 use utils_files::animation_level::AnimationLevel;
 use utils_files::event_source_state::EventSourceState;
-use utils_files::request::{get_request, send_player_amount_update};
+use utils_files::request::{get_request, send_player_amount_update, start_bot_game};
 use utils_files::web_error::ClientError;
 use wasm_bindgen::JsValue;
 use web_sys::HtmlInputElement;
@@ -31,6 +31,7 @@ pub enum PanelMsg {
     SelectAnimationLevel(AnimationLevel),
     ApplySettings,
     Send(u8),
+    PlayBots,
     ClickLink(u64),
     AwaitUpdate,
     Update(Option<Vec<GameListEntry>>),
@@ -139,6 +140,18 @@ impl Component for Panel {
                 });
                 false
             }
+            // One player against a full table of bots, joined straight away
+            Self::Message::PlayBots => {
+                ctx.link().send_future(async move {
+                    match start_bot_game(4, 3).await {
+                        Ok(game_number) => Self::Message::ClickLink(game_number),
+                        Err(error) => Self::Message::Response(
+                            error.push(file!(), "update(): failed to start a bot game"),
+                        ),
+                    }
+                });
+                false
+            }
             Self::Message::ClickLink(entry_number) => {
                 self.event_source.close_connection();
                 match ctx.props().window.location().set_href(&format!(
@@ -247,6 +260,13 @@ impl Panel {
                             "Subtract Player"
                         }</button>
                 </div>
+                <div class={classes!("menu_screen", "font")}>
+                    <button
+                        class={classes!("menu_button", "bot_button")}
+                        onclick={onclick(PanelMsg::PlayBots)}>{
+                            "Play vs 3 Bots"
+                        }</button>
+                </div>
                 <div class={classes!("links_base", "font")}>
                     <ul class={"links_holder"}>{
                         match &self.links {
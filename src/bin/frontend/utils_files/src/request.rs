@@ -45,21 +45,84 @@ pub async fn send_player_amount_update<'a>(number_of_players: u8) -> Result<(),
         })
 }
 
-pub async fn fire_on_position<T: DeserializeOwned + Serialize>(
+pub async fn start_bot_game(number_of_players: u8, bots: u8) -> Result<u64, ClientError> {
+    let mut map = HashMap::new();
+    map.insert("number_of_players".to_string(), number_of_players);
+    map.insert("bots".to_string(), bots);
+    reqwest::Client::new()
+        .post("http://127.0.0.1:8000/start")
+        .json::<HashMap<String, u8>>(&map)
+        .send()
+        .await
+        .map_err(|error: _| {
+            ClientError::from(
+                file!(),
+                "start_bot_game(): failed to send client post request",
+            )
+            .push("", &error.to_string())
+        })?
+        .json::<u64>()
+        .await
+        .map_err(|error: _| {
+            ClientError::from(
+                file!(),
+                "start_bot_game(): failed to parse the new game number",
+            )
+            .push("", &error.to_string())
+        })
+}
+
+pub async fn fire_on_position<T: DeserializeOwned + Serialize, R: DeserializeOwned>(
     item: T,
     game_number: u32,
-) -> Result<(), ClientError> {
-    reqwest::Client::new()
+) -> Result<R, ClientError> {
+    let response = reqwest::Client::new()
         .post(format!("http://127.0.0.1:8000/game/fire/{}", game_number))
         .json::<T>(&item)
         .send()
         .await
-        .map(|_| ())
         .map_err(|error: _| {
             ClientError::from(
                 file!(),
                 "fire_on_position(): Failed to Send Fire Post Request",
             )
             .push("", &error.to_string())
+        })?;
+    if !response.status().is_success() {
+        return Err(
+            ClientError::from(file!(), "fire_on_position(): Fire Request Was Rejected")
+                .push("", &response.text().await.unwrap_or_default()),
+        );
+    }
+    response.json::<R>().await.map_err(|error: _| {
+        ClientError::from(file!(), "fire_on_position(): Failed to parse fire response")
+            .push("", &error.to_string())
+    })
+}
+
+pub async fn submit_fleet_placement<T: DeserializeOwned + Serialize>(
+    item: T,
+    game_number: u32,
+) -> Result<bool, ClientError> {
+    reqwest::Client::new()
+        .post(format!("http://127.0.0.1:8000/game/place/{}", game_number))
+        .json::<T>(&item)
+        .send()
+        .await
+        .map_err(|error: _| {
+            ClientError::from(
+                file!(),
+                "submit_fleet_placement(): Failed to Send Placement Post Request",
+            )
+            .push("", &error.to_string())
+        })?
+        .json::<bool>()
+        .await
+        .map_err(|error: _| {
+            ClientError::from(
+                file!(),
+                "submit_fleet_placement(): Failed to parse placement response",
+            )
+            .push("", &error.to_string())
         })
 }
@@ -1,6 +1,12 @@
+use crate::keys::PlayerKeys;
+use crate::store::{Channel, Counter, GameListUpdate, GameStore, GameUpdate, UPDATE_ATTEMPTS};
+use interact::link::GameList;
+use mechanics::event::GameEvent;
+use mechanics::game::Game;
+use rocket::async_trait;
 use rocket_db_pools::{
-    deadpool_redis::{redis, redis::ToRedisArgs, Pool},
-    Connection, Database,
+    deadpool_redis::{redis, redis::ToRedisArgs, Connection, Pool},
+    Database,
 };
 
 #[derive(Database)]
@@ -8,9 +14,12 @@ use rocket_db_pools::{
 pub struct RedisDatabase(Pool);
 
 pub enum DatabaseOption {
-    GET,
     SET,
-    RENAME,
+    INCR,
+    RENAMENX,
+    RPUSH,
+    WATCH,
+    UNWATCH,
 }
 
 impl std::fmt::Display for DatabaseOption {
@@ -19,9 +28,12 @@ impl std::fmt::Display for DatabaseOption {
             f,
             "{}",
             match self {
-                Self::GET => "GET",
                 Self::SET => "SET",
-                Self::RENAME => "RENAME",
+                Self::INCR => "INCR",
+                Self::RENAMENX => "RENAMENX",
+                Self::RPUSH => "RPUSH",
+                Self::WATCH => "WATCH",
+                Self::UNWATCH => "UNWATCH",
             }
         )
     }
@@ -30,11 +42,11 @@ impl std::fmt::Display for DatabaseOption {
 pub async fn database<T: ToRedisArgs>(
     option: DatabaseOption,
     args: &T,
-    rds: &mut Connection<RedisDatabase>,
+    rds: &mut Connection,
 ) -> Result<String, String> {
     match redis::cmd(&format!("{}", option))
-        .arg(&args)
-        .query_async::<_, String>(&mut **rds)
+        .arg(args)
+        .query_async::<_, String>(rds)
         .await
     {
         Ok(result) => Ok(result),
@@ -48,14 +60,46 @@ pub async fn database<T: ToRedisArgs>(
     }
 }
 
+// INCR, RENAMENX and RPUSH reply with integers, which a String reply refuses
+pub async fn integer_database<T: ToRedisArgs>(
+    option: DatabaseOption,
+    args: &T,
+    rds: &mut Connection,
+) -> Result<i64, String> {
+    match redis::cmd(&format!("{}", option))
+        .arg(args)
+        .query_async::<_, i64>(rds)
+        .await
+    {
+        Ok(result) => Ok(result),
+        Err(error) => Err(format!(
+            "{}, {}: integer_database({}): Redis Cmd Failed to execute option query command; {}",
+            file!(),
+            line!(),
+            option,
+            error
+        )),
+    }
+}
+
+// A connection goes back to the pool still watching unless every way out of
+// a watched read unwatches, and its next transaction would then be refused
+async fn unwatched<T>(result: Result<T, String>, rds: &mut Connection) -> Result<T, String> {
+    let unwatch: Result<String, String> =
+        database(DatabaseOption::UNWATCH, &Vec::<String>::new(), rds).await;
+    let value: T = result?;
+    unwatch?;
+    Ok(value)
+}
+
 pub async fn json_database(
     option: DatabaseOption,
-    args: &Vec<String>,
-    rds: &mut Connection<RedisDatabase>,
+    args: &[String],
+    rds: &mut Connection,
 ) -> Result<String, String> {
     match redis::cmd(&format!("JSON.{}", option))
-        .arg(&args)
-        .query_async::<_, String>(&mut **rds)
+        .arg(args)
+        .query_async::<_, String>(rds)
         .await
     {
         Ok(result) => Ok(result),
@@ -69,3 +113,355 @@ pub async fn json_database(
         )),
     }
 }
+
+// Missing keys come back as None rather than as a failed query
+pub async fn optional_database(
+    command: &str,
+    args: &[String],
+    rds: &mut Connection,
+) -> Result<Option<String>, String> {
+    match redis::cmd(command)
+        .arg(args)
+        .query_async::<_, Option<String>>(rds)
+        .await
+    {
+        Ok(result) => Ok(result),
+        Err(error) => Err(format!(
+            "{}, {}: optional_database({}): Redis Cmd Failed to execute `{}` query command; {}",
+            file!(),
+            line!(),
+            command,
+            command,
+            error
+        )),
+    }
+}
+
+pub async fn list_database(key: &str, rds: &mut Connection) -> Result<Vec<String>, String> {
+    match redis::cmd("LRANGE")
+        .arg(key)
+        .arg(0)
+        .arg(-1)
+        .query_async::<_, Vec<String>>(rds)
+        .await
+    {
+        Ok(result) => Ok(result),
+        Err(error) => Err(format!(
+            "{}, {}: list_database({}): Redis Cmd Failed to execute `LRANGE` query command; {}",
+            file!(),
+            line!(),
+            key,
+            error
+        )),
+    }
+}
+
+// Keeps the original key layout: `game_{id}` and player ids as RedisJSON
+// documents, `current_games` as the lobby list and `game_events_{id}` as lists
+pub struct RedisStore {
+    pool: Pool,
+}
+
+impl RedisStore {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    pub fn from_database(database: &RedisDatabase) -> Self {
+        Self::new(database.0.clone())
+    }
+
+    async fn connection(&self) -> Result<Connection, String> {
+        self.pool.get().await.map_err(|error| {
+            format!(
+                "{}, {}: RedisStore: Failed to get a connection from the pool; {}",
+                file!(),
+                line!(),
+                error
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl GameStore for RedisStore {
+    async fn get_game(&self, game_id: u64) -> Result<Game, String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec![format!("game_{game_id}"), ".".to_string()];
+        match optional_database("JSON.GET", &args, &mut rds).await? {
+            Some(game) => serde_json::from_str(&game).map_err(|error| {
+                format!("{}, {}: get_game({}): {}", file!(), line!(), game_id, error)
+            }),
+            None => Err(format!(
+                "{}, {}: get_game({}): game does not exist",
+                file!(),
+                line!(),
+                game_id
+            )),
+        }
+    }
+
+    async fn put_game(&self, game: &Game) -> Result<(), String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec![
+            format!("game_{}", game.game_number),
+            ".".to_string(),
+            game.into(),
+        ];
+        json_database(DatabaseOption::SET, &args, &mut rds).await?;
+        Ok(())
+    }
+
+    // WATCH makes EXEC fail if anyone wrote the game after it was read, in
+    // which case the change is run again on what they left behind
+    async fn update_game(&self, game_id: u64, update: &mut GameUpdate<'_>) -> Result<Game, String> {
+        let mut rds: Connection = self.connection().await?;
+        let game_tag: String = format!("game_{game_id}");
+        for _ in 0..UPDATE_ATTEMPTS {
+            database(DatabaseOption::WATCH, &game_tag, &mut rds).await?;
+            let args: Vec<String> = vec![game_tag.clone(), ".".to_string()];
+            let read: Result<Option<String>, String> =
+                optional_database("JSON.GET", &args, &mut rds).await;
+            let mut game: Game = match read {
+                Ok(Some(game)) => match serde_json::from_str(&game) {
+                    Ok(game) => game,
+                    Err(error) => {
+                        let error: String = format!(
+                            "{}, {}: update_game({}): {}",
+                            file!(),
+                            line!(),
+                            game_id,
+                            error
+                        );
+                        return unwatched(Err(error), &mut rds).await;
+                    }
+                },
+                Ok(None) => {
+                    let error: String = format!(
+                        "{}, {}: update_game({}): game does not exist",
+                        file!(),
+                        line!(),
+                        game_id
+                    );
+                    return unwatched(Err(error), &mut rds).await;
+                }
+                Err(error) => return unwatched(Err(error), &mut rds).await,
+            };
+            if !update(&mut game) {
+                return unwatched(Ok(game), &mut rds).await;
+            }
+            let events: Vec<String> = game
+                .take_events()
+                .iter()
+                .map(|event: &GameEvent| serde_json::to_string(event).unwrap())
+                .collect::<Vec<String>>();
+            let mut transaction: redis::Pipeline = redis::pipe();
+            transaction
+                .atomic()
+                .cmd("JSON.SET")
+                .arg(&game_tag)
+                .arg(".")
+                .arg(String::from(&game))
+                .ignore();
+            if !events.is_empty() {
+                transaction
+                    .cmd("RPUSH")
+                    .arg(format!("game_events_{game_id}"))
+                    .arg(events)
+                    .ignore();
+            }
+            match transaction.query_async::<_, Option<()>>(&mut rds).await {
+                Ok(Some(())) => return Ok(game),
+                Ok(None) => continue,
+                Err(error) => {
+                    let error: String = format!(
+                        "{}, {}: update_game({}): Redis Transaction Failed to execute; {}",
+                        file!(),
+                        line!(),
+                        game_id,
+                        error
+                    );
+                    return unwatched(Err(error), &mut rds).await;
+                }
+            }
+        }
+        Err(format!(
+            "{}, {}: update_game({}): still contended after {} attempts",
+            file!(),
+            line!(),
+            game_id,
+            UPDATE_ATTEMPTS
+        ))
+    }
+
+    async fn get_player_keys(&self, player_id: &str) -> Result<PlayerKeys, String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec![player_id.to_string(), ".".to_string()];
+        match optional_database("JSON.GET", &args, &mut rds).await? {
+            Some(keys) => serde_json::from_str(&keys).map_err(|error| {
+                format!(
+                    "{}, {}: get_player_keys({}): {}",
+                    file!(),
+                    line!(),
+                    player_id,
+                    error
+                )
+            }),
+            None => Err(format!(
+                "{}, {}: get_player_keys({}): player is not registered",
+                file!(),
+                line!(),
+                player_id
+            )),
+        }
+    }
+
+    async fn claim_player(&self, player_id: &str, keys: &PlayerKeys) -> Result<bool, String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec![
+            player_id.to_string(),
+            ".".to_string(),
+            keys.into(),
+            "NX".to_string(),
+        ];
+        Ok(optional_database("JSON.SET", &args, &mut rds)
+            .await?
+            .is_some())
+    }
+
+    async fn rename_player(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut rds: Connection = self.connection().await?;
+        if integer_database(DatabaseOption::RENAMENX, &vec![from, to], &mut rds).await? == 0 {
+            return Err(format!(
+                "{}, {}: rename_player({}): {} is already taken",
+                file!(),
+                line!(),
+                from,
+                to
+            ));
+        }
+        Ok(())
+    }
+
+    async fn list_games(&self) -> Result<GameList, String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec!["current_games".to_string(), ".".to_string()];
+        match optional_database("JSON.GET", &args, &mut rds).await? {
+            Some(games) => serde_json::from_str(&games)
+                .map_err(|error| format!("{}, {}: list_games(): {}", file!(), line!(), error)),
+            None => Ok(GameList::new()),
+        }
+    }
+
+    async fn update_games(&self, update: &mut GameListUpdate<'_>) -> Result<(), String> {
+        let mut rds: Connection = self.connection().await?;
+        let args: Vec<String> = vec!["current_games".to_string(), ".".to_string()];
+        for _ in 0..UPDATE_ATTEMPTS {
+            database(DatabaseOption::WATCH, &"current_games", &mut rds).await?;
+            let read: Result<Option<String>, String> =
+                optional_database("JSON.GET", &args, &mut rds).await;
+            let mut games: GameList = match read {
+                Ok(Some(games)) => match serde_json::from_str(&games) {
+                    Ok(games) => games,
+                    Err(error) => {
+                        let error: String =
+                            format!("{}, {}: update_games(): {}", file!(), line!(), error);
+                        return unwatched(Err(error), &mut rds).await;
+                    }
+                },
+                Ok(None) => GameList::new(),
+                Err(error) => return unwatched(Err(error), &mut rds).await,
+            };
+            update(&mut games);
+            let mut transaction: redis::Pipeline = redis::pipe();
+            transaction
+                .atomic()
+                .cmd("JSON.SET")
+                .arg("current_games")
+                .arg(".")
+                .arg(serde_json::to_string(&games).unwrap())
+                .ignore();
+            match transaction.query_async::<_, Option<()>>(&mut rds).await {
+                Ok(Some(())) => return Ok(()),
+                Ok(None) => continue,
+                Err(error) => {
+                    let error: String = format!(
+                        "{}, {}: update_games(): Redis Transaction Failed to execute; {}",
+                        file!(),
+                        line!(),
+                        error
+                    );
+                    return unwatched(Err(error), &mut rds).await;
+                }
+            }
+        }
+        Err(format!(
+            "{}, {}: update_games(): still contended after {} attempts",
+            file!(),
+            line!(),
+            UPDATE_ATTEMPTS
+        ))
+    }
+
+    async fn increment(&self, counter: Counter) -> Result<u64, String> {
+        let mut rds: Connection = self.connection().await?;
+        let count: i64 =
+            integer_database(DatabaseOption::INCR, &counter.to_string(), &mut rds).await?;
+        u64::try_from(count).map_err(|error| {
+            format!(
+                "{}, {}: increment({}): {}",
+                file!(),
+                line!(),
+                counter,
+                error
+            )
+        })
+    }
+
+    async fn append_events(&self, game_id: u64, events: &[GameEvent]) -> Result<(), String> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut rds: Connection = self.connection().await?;
+        let mut args: Vec<String> = vec![format!("game_events_{game_id}")];
+        args.extend(
+            events
+                .iter()
+                .map(|event: &GameEvent| serde_json::to_string(event).unwrap()),
+        );
+        integer_database(DatabaseOption::RPUSH, &args, &mut rds).await?;
+        Ok(())
+    }
+
+    async fn events(&self, game_id: u64) -> Result<Vec<GameEvent>, String> {
+        let mut rds: Connection = self.connection().await?;
+        list_database(&format!("game_events_{game_id}"), &mut rds)
+            .await?
+            .iter()
+            .map(|event: &String| serde_json::from_str(event))
+            .collect::<Result<Vec<GameEvent>, serde_json::Error>>()
+            .map_err(|error| format!("{}, {}: events({}): {}", file!(), line!(), game_id, error))
+    }
+
+    async fn notify(&self, channel: Channel) -> Result<(), String> {
+        let mut rds: Connection = self.connection().await?;
+        integer_database(DatabaseOption::INCR, &channel.to_string(), &mut rds).await?;
+        Ok(())
+    }
+
+    async fn notification_count(&self, channel: Channel) -> Result<u64, String> {
+        let mut rds: Connection = self.connection().await?;
+        match optional_database("GET", &[channel.to_string()], &mut rds).await? {
+            Some(count) => count.parse::<u64>().map_err(|error| {
+                format!(
+                    "{}, {}: notification_count({}): {}",
+                    file!(),
+                    line!(),
+                    channel,
+                    error
+                )
+            }),
+            None => Ok(0),
+        }
+    }
+}
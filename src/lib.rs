@@ -1,2 +1,4 @@
+pub mod database;
 pub mod start;
 pub mod keys;
+pub mod store;
@@ -2,44 +2,60 @@
 #[macro_use]
 extern crate rocket;
 
-use crate::database::database;
-use crate::database::DatabaseOption;
-use crate::database::RedisDatabase;
+use battleship::database::{RedisDatabase, RedisStore};
 use battleship::keys::PlayerKeys;
 use battleship::start;
-use database::json_database;
+use battleship::store::{register_new_player, Channel, Counter, MemoryStore, SharedStore};
 use ecies::decrypt;
 use interact::link::{GameList, GameListEntry};
 use interact::site::SITE_LINK;
-use mechanics::game::Game;
-use mechanics::position::FirePosition;
-use rand::{distributions::Alphanumeric, Rng};
-use rocket::Shutdown;
+use mechanics::ai::Difficulty;
+use mechanics::board::CompactBoard;
+use mechanics::event::GameEvent;
+use mechanics::game::{Game, GamePhase};
+use mechanics::notation;
+use mechanics::position::{FireError, FireOutcome, FirePosition, Target};
+use mechanics::rules::GameRules;
+use mechanics::ship::{FleetPlacement, FleetPreset};
+use rocket::http::Status;
 use rocket::{
     fairing::AdHoc,
     fs::NamedFile,
     response::{
-        status::NotFound,
+        status::{Custom, NotFound},
         stream::{Event, EventStream},
         Redirect,
     },
     serde::json::Json,
-    tokio::select,
+    tokio::{select, time::sleep},
 };
-use rocket_db_pools::{Connection, Database};
+use rocket::{Shutdown, State};
+use rocket_db_pools::Database;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::path::PathBuf;
-
-pub mod database;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize)]
-struct NumberOfPlayers {
-    number_of_players: i8,
+struct GameSettings {
+    number_of_players: usize,
+    #[serde(default)]
+    rules: GameRules,
+    #[serde(default)]
+    fleet_preset: Option<FleetPreset>,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    difficulty: Difficulty,
+    // Seats taken by bots, leaving at least one for a player
+    #[serde(default)]
+    bots: usize,
 }
 
 const MAIN_DIR: &str = "src/bin/frontend/main_page/";
 const BOARD_DIR: &str = "src/bin/frontend/board_page/";
+// How long the event streams wait before checking an empty channel again
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 // Utility Functions
 async fn return_file(item: String) -> Result<NamedFile, NotFound<String>> {
@@ -48,6 +64,92 @@ async fn return_file(item: String) -> Result<NamedFile, NotFound<String>> {
         .map_err(|e| NotFound(e.to_string()))
 }
 
+// Malformed requests are the client's fault, the rest are lost races with the game state
+fn fire_error_status(error: &FireError) -> Status {
+    match error {
+        FireError::UnknownPlayer(_)
+        | FireError::UnknownTarget(_)
+        | FireError::SelfTarget(_)
+        | FireError::OutOfBounds { .. }
+        | FireError::EmptySalvo => Status::BadRequest,
+        FireError::NotInProgress
+        | FireError::Eliminated(_)
+        | FireError::OutOfTurn(_)
+        | FireError::NoShotsRemaining { .. }
+        | FireError::TargetEliminated(_)
+        | FireError::AlreadyFired(_) => Status::Conflict,
+    }
+}
+
+async fn check_access_key(player_id: &str, access_key: &str, store: &SharedStore) -> bool {
+    let decrypt_key: Vec<u8> = match store.get_player_keys(player_id).await {
+        Ok(player_keys) => player_keys.decryption_key,
+        Err(err) => {
+            println!("{}", err);
+            return false;
+        }
+    };
+    let vec_access_key: Vec<u8> = (0..access_key.len() / 2)
+        .filter_map(|index: usize| {
+            u8::from_str_radix(&access_key[(2 * index)..(2 * index) + 2], 16).ok()
+        })
+        .collect::<Vec<u8>>();
+    match decrypt(&decrypt_key, &vec_access_key) {
+        Ok(message) => std::str::from_utf8(&message).unwrap_or("").eq("Request"),
+        Err(_) => false,
+    }
+}
+
+async fn remove_current_game(game_number: u64, store: &SharedStore) {
+    store
+        .update_games(&mut |current_games: &mut GameList| {
+            current_games.retain(|entry: &GameListEntry| entry.game_record_number != game_number)
+        })
+        .await
+        .unwrap();
+}
+
+// Appends to the game's event log, which is never rewritten unlike the game record
+async fn append_events(
+    game_id: u64,
+    events: Vec<GameEvent>,
+    store: &SharedStore,
+) -> Result<(), Custom<String>> {
+    store
+        .append_events(game_id, &events)
+        .await
+        .map_err(|error: String| Custom(Status::InternalServerError, error))
+}
+
+// Bots fire through the same salvo rules as players, just without a challenge.
+// Their shots are only logged once the update holding them has been saved
+fn report_bots(game_state: &Game, game_id: u32, bot_shots: Vec<(usize, Target, FireOutcome)>) {
+    for (from, target, outcome) in bot_shots {
+        println!(
+            "Game {game_id}: {} fired on {}'s ({}, {}): {}",
+            game_state.player_tags[from],
+            game_state.player_tags[target.to],
+            target.lon,
+            target.lat,
+            outcome
+        );
+    }
+}
+
+async fn finish_game(game_state: &Game, game_id: u32, store: &SharedStore) {
+    if let GamePhase::Finished { winners } = &game_state.phase {
+        println!(
+            "Game {game_id}: finished, won by {:?}",
+            winners
+                .iter()
+                .map(|index: &usize| game_state.player_tags[*index].clone())
+                .collect::<Vec<String>>()
+        );
+        remove_current_game(game_state.game_number, store).await;
+        store.notify(Channel::Links).await.unwrap();
+    }
+}
+
 #[get("/<path..>")]
 async fn extra_files(path: PathBuf) -> Result<NamedFile, NotFound<String>> {
     return_file(format!("src/bin/frontend/extra_files/{}", path.display())).await
@@ -59,132 +161,140 @@ async fn intercept_start() -> Redirect {
     Redirect::to(format!("{SITE_LINK}/main"))
 }
 
+async fn register_player(player_id: &str, store: &SharedStore) -> Result<PlayerKeys, String> {
+    let player_keys: PlayerKeys = PlayerKeys::new();
+    if !store.claim_player(player_id, &player_keys).await? {
+        return Err(format!("register_player: {player_id} is already taken"));
+    }
+    player_keys.log(player_id);
+    Ok(player_keys)
+}
+
 //TODO: Perhaps create a unique hashing function that allows the player_id to
 //be securely hidden from the client side
 #[get("/get_player_id")]
-async fn get_player_id(mut rds: Connection<RedisDatabase>) -> Json<(String, String)> {
-    let res: u32 = database(DatabaseOption::GET, &"player_id_count", &mut rds)
-        .await
-        .unwrap_or("0".to_string())
-        .parse::<u32>()
-        .unwrap();
-    let player_index: String = format!("player_{res}");
-    database(
-        DatabaseOption::SET,
-        &vec!["player_id_count", (res + 1).to_string().as_str()],
-        &mut rds,
-    )
-    .await
-    .unwrap();
+async fn get_player_id(store: &State<SharedStore>) -> Json<(String, String)> {
     let player_keys: PlayerKeys = PlayerKeys::new();
+    let player_index: String = register_new_player(store, &player_keys).await.unwrap();
     player_keys.log(&player_index);
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            player_index.clone().to_string(),
-            ".".to_string(),
-            (&player_keys).into(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
     Json((player_index, player_keys.public_key_string()))
 }
 
 #[post("/change_player_id", format = "json", data = "<player_id_change>")]
-async fn change_player_id(mut rds: Connection<RedisDatabase>, player_id_change: Json<Vec<String>>) {
-    database(
-        DatabaseOption::RENAME,
-        &player_id_change.into_inner(),
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    return;
+async fn change_player_id(
+    store: &State<SharedStore>,
+    player_id_change: Json<Vec<String>>,
+) -> Result<(), Custom<String>> {
+    let player_id_change: Vec<String> = player_id_change.into_inner();
+    if player_id_change.len() != 2 {
+        return Err(Custom(
+            Status::BadRequest,
+            "change_player_id: expected the old and the new player id".to_string(),
+        ));
+    }
+    // Ids end up in game records, which have to hold each one on a single line
+    if !notation::is_valid_player_id(&player_id_change[1]) {
+        return Err(Custom(
+            Status::BadRequest,
+            format!(
+                "change_player_id: {:?} is not a usable player id",
+                player_id_change[1]
+            ),
+        ));
+    }
+    // Bot ids are handed out by /start, which fails if a player got there first
+    if player_id_change[1].starts_with("bot_") {
+        return Err(Custom(
+            Status::BadRequest,
+            "change_player_id: ids starting with bot_ are kept for bots".to_string(),
+        ));
+    }
+    // Renaming onto a taken id would hand that player's keys to someone else
+    store
+        .rename_player(&player_id_change[0], &player_id_change[1])
+        .await
+        .map_err(|error: String| Custom(Status::Conflict, error))
 }
 
-#[post("/start", format = "json", data = "<players_obj>")]
-async fn start_game(mut rds: Connection<RedisDatabase>, players_obj: Json<HashMap<String, u8>>) {
+#[post("/start", format = "json", data = "<game_settings_json>")]
+async fn start_game(
+    store: &State<SharedStore>,
+    game_settings_json: Json<GameSettings>,
+) -> Result<Json<u64>, Custom<String>> {
+    let mut game_settings: GameSettings = game_settings_json.into_inner();
+    let number_of_players: usize = game_settings.number_of_players;
+    if let Some(fleet_preset) = game_settings.fleet_preset {
+        game_settings.rules.fleet = fleet_preset.fleet();
+    }
+    game_settings
+        .rules
+        .validate(number_of_players)
+        .map_err(|error: String| Custom(Status::BadRequest, error))?;
+    if number_of_players <= game_settings.bots {
+        return Err(Custom(
+            Status::BadRequest,
+            format!(
+                "start_game: {} bots leave no seat for a player in a {} player game",
+                game_settings.bots, number_of_players
+            ),
+        ));
+    }
+    // Bot fleets come from the seed, so knowing it would show players where they are
+    if game_settings.seed.is_some() && 0 < game_settings.bots {
+        return Err(Custom(
+            Status::BadRequest,
+            "start_game: a seeded game cannot seat bots".to_string(),
+        ));
+    }
     //Updating EventStream call
-    database(DatabaseOption::SET, &vec!["links_update", "true"], &mut rds)
-        .await
-        .unwrap();
+    store.notify(Channel::Links).await.unwrap();
     //Updating Game Count Record
-    let number_of_players: usize = players_obj.into_inner()["number_of_players"] as usize;
-    let mut game_count: u64 = database(DatabaseOption::GET, &"game_count", &mut rds)
-        .await
-        .unwrap_or("0".to_string())
-        .parse::<u64>()
-        .unwrap();
-    game_count += 1;
-    let new_game: Game = Game::new(number_of_players, game_count);
-    database(
-        DatabaseOption::SET,
-        &vec!["game_count", game_count.to_string().as_str()],
-        &mut rds,
-    )
-    .await
-    .unwrap();
+    let game_count: u64 = store.increment(Counter::Games).await.unwrap();
+    // Passing the seed from a bug report replays its fleets, challenge and turn order
+    let mut new_game: Game = match game_settings.seed {
+        Some(seed) => Game::new_with_seed(number_of_players, game_count, game_settings.rules, seed),
+        None => Game::new(number_of_players, game_count, game_settings.rules),
+    }
+    .map_err(|error: String| Custom(Status::BadRequest, error))?;
+    new_game.difficulty = game_settings.difficulty;
+    let mut game_entry: GameListEntry = GameListEntry::new(game_count, number_of_players);
+    for index in 0..game_settings.bots {
+        let bot_id: String = format!("bot_{game_count}_{index}");
+        register_player(&bot_id, store)
+            .await
+            .map_err(|error: String| Custom(Status::Conflict, error))?;
+        new_game.join_bot(bot_id.clone()).unwrap();
+        game_entry = game_entry.add_player(bot_id).unwrap();
+    }
     //Actually Setting Up the Game
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            format!("game_{}", game_count),
-            ".".to_string(),
-            (&new_game).into(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    database(
-        DatabaseOption::SET,
-        &vec!["main_page_update", "true"],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    let mut current_games: GameList = serde_json::from_str(
-        &json_database(
-            DatabaseOption::GET,
-            &vec!["current_games".to_string(), ".".to_string()],
-            &mut rds,
-        )
+    store.put_game(&new_game).await.unwrap();
+    append_events(game_count, new_game.take_events(), store).await?;
+    store.notify(Channel::MainPage).await.unwrap();
+    store
+        .update_games(&mut |current_games: &mut GameList| current_games.push(game_entry.clone()))
         .await
-        .unwrap_or("".to_string()),
-    )
-    .unwrap();
-    current_games.push(GameListEntry::new(game_count, number_of_players));
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            "current_games".to_string(),
-            ".".to_string(),
-            serde_json::to_string(&current_games).unwrap(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
+        .unwrap();
+    Ok(Json(game_count))
 }
 
 #[get("/page_stream")]
-async fn get_page_stream(
-    mut rds: Connection<RedisDatabase>,
-    mut shutdown: Shutdown,
-) -> EventStream![] {
+async fn get_page_stream(store: &State<SharedStore>, mut shutdown: Shutdown) -> EventStream![] {
+    let store: SharedStore = store.inner().clone();
     EventStream! {
+        let mut seen: u64 = store.notification_count(Channel::Links).await.unwrap_or(0);
         loop {
             select! {
                 _ = &mut shutdown => {
                     yield Event::data("end");
                     break;
                 }
-                value = database(DatabaseOption::GET, &"links_update", &mut rds) => {
-                    if value.unwrap_or("false".to_string()).eq("true") {
-                        database(DatabaseOption::SET, &vec!["links_update", "false"], &mut rds).await.unwrap();
-                        yield Event::data("");
+                count = store.notification_count(Channel::Links) => {
+                    match count {
+                        Ok(count) if count != seen => {
+                            seen = count;
+                            yield Event::data("");
+                        }
+                        _ => sleep(POLL_INTERVAL).await,
                     }
                 }
             }
@@ -193,15 +303,9 @@ async fn get_page_stream(
 }
 
 #[get("/active_game_links")]
-async fn get_active_game_links(mut rds: Connection<RedisDatabase>) -> Result<String, String> {
-    match json_database(
-        DatabaseOption::GET,
-        &vec!["current_games".to_string(), ".".to_string()],
-        &mut rds,
-    )
-    .await
-    {
-        Ok(result) => Ok(result),
+async fn get_active_game_links(store: &State<SharedStore>) -> Result<String, String> {
+    match store.list_games().await {
+        Ok(result) => Ok(serde_json::to_string(&result).unwrap()),
         Err(error) => {
             println!("{}", error);
             Err(error)
@@ -212,188 +316,149 @@ async fn get_active_game_links(mut rds: Connection<RedisDatabase>) -> Result<Str
 // Game Page Functions
 #[get("/<game_id>/<player_id>")]
 async fn process_game_request(
-    mut rds: Connection<RedisDatabase>,
+    store: &State<SharedStore>,
     game_id: u32,
     player_id: String,
 ) -> Result<NamedFile, NotFound<String>> {
-    let game_tag: String = format!("game_{game_id}");
-    let mut game_state: Game = match json_database(
-        DatabaseOption::GET,
-        &vec![format!("game_{game_id}"), ".".to_string()],
-        &mut rds,
-    )
-    .await
+    // Joining runs as one update, so players arriving together cannot drop each other
+    let mut joined: bool = false;
+    let game_state: Game = match store
+        .update_game(game_id as u64, &mut |game_state: &mut Game| -> bool {
+            joined = false;
+            // Passing game information if already joined or spectating
+            if game_state.player_tags.contains(&player_id) {
+                return false;
+            }
+            if let Err(error) = game_state.join(player_id.clone()) {
+                println!("{}", error);
+                return false;
+            }
+            if game_state.phase == GamePhase::Placement {
+                game_state.place_bot_fleets().unwrap();
+            }
+            joined = true;
+            true
+        })
+        .await
     {
-        Ok(boards) => serde_json::from_str(&boards).unwrap(),
+        Ok(game_state) => game_state,
         Err(error) => {
             println!("{}", error);
             panic!()
         }
     };
-    // Passing game information if it is already filled
-    if game_state.player_tags.len() == game_state.number_of_players
-        || game_state.player_tags.contains(&player_id)
-    {
+    if !joined {
         return return_file(format!("{BOARD_DIR}dist/index.html")).await;
     }
-    game_state.player_tags.push(player_id);
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            "current_games".to_string(),
-            format!(".[{}].active_player_names", game_id - 1),
-            serde_json::to_string(&game_state.player_tags).unwrap(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    if game_state.player_tags.len() < game_state.number_of_players {
-        json_database(
-            DatabaseOption::SET,
-            &vec![
-                game_tag,
-                ".".to_string(),
-                serde_json::to_string(&game_state).unwrap(),
-            ],
-            &mut rds,
-        )
+    store
+        .update_games(&mut |current_games: &mut GameList| {
+            for entry in current_games.iter_mut() {
+                if entry.game_record_number == game_id as u64 {
+                    if let Ok(updated) = entry.add_player(player_id.clone()) {
+                        *entry = updated;
+                    }
+                }
+            }
+        })
         .await
         .unwrap();
+    if game_state.phase == GamePhase::Lobby {
         return return_file(format!("{BOARD_DIR}dist/index.html")).await;
     }
-    // Kick-off the game by creating firing create challenge
-    game_state.challenge = rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(10)
-        .map(char::from)
-        .collect::<String>();
-    game_state.boards.start_board();
-    database(
-        DatabaseOption::SET,
-        &vec![format!("game_update_{game_id}").as_str(), "true"],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            game_tag,
-            ".".to_string(),
-            serde_json::to_string(&game_state).unwrap(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    database(DatabaseOption::SET, &vec!["links_update", "true"], &mut rds)
-        .await
-        .unwrap();
+    // Lobby is full and the placement phase is open; the firing challenge is
+    // only created once every player has submitted their fleet
+    store.notify(Channel::Game(game_id as u64)).await.unwrap();
+    store.notify(Channel::Links).await.unwrap();
     return_file(format!("{BOARD_DIR}dist/index.html")).await
 }
 
 #[get("/<game_id>/<player_id>/<access_key>")]
 async fn get_game_state(
-    mut rds: Connection<RedisDatabase>,
+    store: &State<SharedStore>,
     game_id: u32,
     player_id: String,
     access_key: String,
-) -> Json<(String, String, String, String)> {
-    let game_state: Game = match json_database(
-        DatabaseOption::GET,
-        &vec![format!("game_{game_id}"), ".".to_string()],
-        &mut rds,
-    )
-    .await
-    {
-        Ok(boards) => serde_json::from_str(&boards).unwrap(),
+) -> Json<(String, String, String, String, String, String, String)> {
+    let game_state: Game = match store.get_game(game_id as u64).await {
+        Ok(game_state) => game_state,
         Err(error) => {
             println!("{}", error);
             panic!()
         }
     };
     let player_tags_string: String = serde_json::to_string(&game_state.player_tags).unwrap();
+    let phase_string: String = serde_json::to_string(&game_state.phase).unwrap();
+    let turn_string: String = serde_json::to_string(&game_state.turn_state()).unwrap();
+    let rules_string: String = serde_json::to_string(&game_state.boards.rules).unwrap();
     // Spector mode if already filled
-    if !game_state.player_tags.contains(&player_id)
-        && game_state.player_tags.len() == game_state.number_of_players
-    {
+    if !game_state.player_tags.contains(&player_id) && game_state.phase != GamePhase::Lobby {
         return Json((
             "".to_string(),
             player_tags_string,
             "".to_string(),
-            serde_json::to_string(&game_state.boards.positions).unwrap(),
+            serde_json::to_string(&CompactBoard::from_positions(&game_state.boards.positions))
+                .unwrap(),
+            phase_string,
+            turn_string,
+            rules_string,
         ));
     }
-    let decrypt_key: Vec<u8> = serde_json::from_str(
-        match &json_database(
-            DatabaseOption::GET,
-            &vec![player_id.clone(), ".decryption_key".to_string()],
-            &mut rds,
-        )
-        .await
-        {
-            Ok(string) => string,
-            Err(err) => {
-                println!("{}", err);
-                ""
-            }
-        },
-    )
-    .unwrap();
-    let vec_access_key: Vec<u8> = (0..access_key.len() / 2)
-        .map(|index: usize| {
-            i64::from_str_radix(&access_key[(2 * index)..(2 * index) + 2], 16).unwrap() as u8
-        })
-        .collect::<Vec<u8>>();
-    let key_result: String = std::str::from_utf8(&decrypt(&decrypt_key, &vec_access_key).unwrap())
-        .unwrap()
-        .to_string();
-    if !key_result.eq("Request") || game_state.challenge.eq("") {
-        println!("Key Failed to Triggered for {player_id}: Key {key_result}");
+    let player_index: Option<usize> = game_state.player_tags.iter().position(|x| player_id.eq(x));
+    if player_index.is_none() || !check_access_key(&player_id, &access_key, store).await {
+        println!("Key Failed to Triggered for {player_id}");
         return Json((
             game_state.challenge,
             player_tags_string,
             "".to_string(),
-            serde_json::to_string(&game_state.boards.positions).unwrap(),
+            serde_json::to_string(&CompactBoard::from_positions(&game_state.boards.positions))
+                .unwrap(),
+            phase_string,
+            turn_string,
+            rules_string,
         ));
     }
-    let player_index: usize = game_state
-        .player_tags
-        .iter()
-        .position(|x| player_id.eq(x))
-        .unwrap();
+    let player_index: usize = player_index.unwrap();
     Json((
         game_state.challenge,
         player_tags_string,
         serde_json::to_string(&game_state.boards.ship_set[player_index]).unwrap(),
-        serde_json::to_string(
+        serde_json::to_string(&CompactBoard::from_positions(
             &game_state
                 .boards
                 .get_board_with_player_positions(player_index),
-        )
+        ))
         .unwrap(),
+        phase_string,
+        turn_string,
+        rules_string,
     ))
 }
 
 #[get("/<game_number>/game_stream")]
 async fn get_game_stream(
-    mut rds: Connection<RedisDatabase>,
+    store: &State<SharedStore>,
     mut shutdown: Shutdown,
-    game_number: usize,
+    game_number: u64,
 ) -> EventStream![] {
-    let game_tag: String = format!("game_update_{game_number}");
+    let store: SharedStore = store.inner().clone();
     EventStream! {
+        let mut seen: u64 = store
+            .notification_count(Channel::Game(game_number))
+            .await
+            .unwrap_or(0);
         loop {
             select! {
                 _ = &mut shutdown => {
                     yield Event::data("end");
                     break;
                 }
-                value = database(DatabaseOption::GET, &game_tag, &mut rds) => {
-                    if value.unwrap_or("false".to_string()).eq("true") {
-                        database(DatabaseOption::SET, &vec![game_tag.as_str(), "false"], &mut rds).await.unwrap();
-                        yield Event::data("");
+                count = store.notification_count(Channel::Game(game_number)) => {
+                    match count {
+                        Ok(count) if count != seen => {
+                            seen = count;
+                            yield Event::data("");
+                        }
+                        _ => sleep(POLL_INTERVAL).await,
                     }
                 }
             }
@@ -401,89 +466,229 @@ async fn get_game_stream(
     }
 }
 
-//TODO: Find a way to allow turn-progression tracking on the backend
-//and inhibit fire request if true
+#[post("/place/<game_id>", format = "json", data = "<fleet_placement_json>")]
+async fn place_fleet(
+    store: &State<SharedStore>,
+    fleet_placement_json: Json<FleetPlacement>,
+    game_id: u32,
+) -> Json<bool> {
+    let fleet_placement: FleetPlacement = fleet_placement_json.into_inner();
+    let game_state: Game = match store.get_game(game_id as u64).await {
+        Ok(game_state) => game_state,
+        Err(error) => {
+            println!("{}", error);
+            return Json(false);
+        }
+    };
+    let player_id: String = match game_state.player_tags.get(fleet_placement.from) {
+        Some(player_id) => player_id.clone(),
+        None => return Json(false),
+    };
+    if !check_access_key(&player_id, &fleet_placement.access_key, store).await {
+        return Json(false);
+    }
+    let (mut placed, mut started) = (false, false);
+    let mut bot_shots: Vec<(usize, Target, FireOutcome)> = Vec::new();
+    let game_state: Game = match store
+        .update_game(game_id as u64, &mut |game_state: &mut Game| -> bool {
+            (placed, started) = (false, false);
+            bot_shots.clear();
+            if let Err(error) =
+                game_state.place_fleet(fleet_placement.from, fleet_placement.ships.clone())
+            {
+                println!("{}", error);
+                return false;
+            }
+            placed = true;
+            // Kick-off the game by creating firing challenge
+            if game_state.all_fleets_placed() {
+                let challenge: String = game_state.new_challenge();
+                game_state.start(challenge).unwrap();
+                bot_shots = game_state.play_bots();
+                started = true;
+            }
+            true
+        })
+        .await
+    {
+        Ok(game_state) => game_state,
+        Err(error) => {
+            println!("{}", error);
+            return Json(false);
+        }
+    };
+    if !placed {
+        return Json(false);
+    }
+    if started {
+        report_bots(&game_state, game_id, bot_shots);
+        finish_game(&game_state, game_id, store).await;
+        store.notify(Channel::Game(game_id as u64)).await.unwrap();
+    }
+    Json(true)
+}
+
 #[post("/fire/<game_id>", format = "json", data = "<fire_position_json>")]
 async fn fire(
-    mut rds: Connection<RedisDatabase>,
+    store: &State<SharedStore>,
     fire_position_json: Json<FirePosition>,
     game_id: u32,
-) -> Json<bool> {
+) -> Result<Json<Vec<FireOutcome>>, Custom<String>> {
     let fire_position: FirePosition = fire_position_json.into_inner();
-    let game_tag: String = format!("game_{game_id}");
-    let mut game_state: Game = match json_database(
-        DatabaseOption::GET,
-        &vec![game_tag.clone(), ".".to_string()],
-        &mut rds,
-    )
-    .await
-    {
-        Ok(boards) => serde_json::from_str(&boards).unwrap(),
+    let game_state: Game = match store.get_game(game_id as u64).await {
+        Ok(game_state) => game_state,
         Err(error) => {
             println!("{}", error);
-            panic!()
+            return Err(Custom(
+                Status::NotFound,
+                format!("fire: game {game_id} does not exist"),
+            ));
         }
     };
-    println!("branch 1: {:b}", game_state.shot_list);
-    if (game_state.shot_list & (1 << fire_position.from)) != 0 {
-        return Json(false);
+    if let Err(error) = game_state.validate_salvo(fire_position.from, &fire_position.targets) {
+        println!("Game {game_id}: rejected fire request: {error}");
+        return Err(Custom(
+            fire_error_status(&error),
+            format!("fire: game {game_id}: {error}"),
+        ));
     }
-    let decrypt_key: Vec<u8> = serde_json::from_str(
-        &json_database(
-            DatabaseOption::GET,
-            &vec![
-                game_state.player_tags[fire_position.from].clone(),
-                ".decryption_key".to_string(),
-            ],
-            &mut rds,
-        )
+    let decrypt_key: Vec<u8> = match store
+        .get_player_keys(&game_state.player_tags[fire_position.from])
         .await
-        .unwrap(),
-    )
-    .unwrap();
-    println!("branch 2: {:b}", game_state.shot_list);
-    if !game_state.challenge.eq(&std::str::from_utf8(
-        &decrypt(&decrypt_key, &fire_position.challenge).unwrap(),
-    )
-    .unwrap())
     {
-        return Json(false);
+        Ok(player_keys) => player_keys.decryption_key,
+        Err(error) => {
+            println!("{}", error);
+            Vec::new()
+        }
+    };
+    let challenge: Option<String> = decrypt(&decrypt_key, &fire_position.challenge)
+        .ok()
+        .and_then(|bytes: Vec<u8>| String::from_utf8(bytes).ok());
+    if challenge.as_ref() != Some(&game_state.challenge) {
+        return Err(Custom(
+            Status::Forbidden,
+            format!("fire: game {game_id}: the challenge could not be verified"),
+        ));
     }
-    game_state.shot_list = game_state.shot_list | (1 << fire_position.from);
-    println!("branch 3: {:b}", game_state.shot_list);
-    game_state.boards.positions = game_state
-        .boards
-        .fire(fire_position.lon, fire_position.lat, fire_position.to)
-        .unwrap();
-    if game_state.shot_list
-        ^ (2_u32
-            .checked_pow(game_state.number_of_players as u32)
-            .unwrap()
-            - 1)
-        == 0
+    // The checks above ran on a snapshot; the salvo itself is validated again
+    // against whatever state the update lands on
+    let mut result: Result<Vec<FireOutcome>, FireError> = Err(FireError::NotInProgress);
+    let mut bot_shots: Vec<(usize, Target, FireOutcome)> = Vec::new();
+    let game_state: Game = match store
+        .update_game(game_id as u64, &mut |game_state: &mut Game| -> bool {
+            bot_shots.clear();
+            result = game_state.fire_salvo(fire_position.from, &fire_position.targets);
+            if result.is_err() {
+                return false;
+            }
+            game_state.advance_round();
+            bot_shots = game_state.play_bots();
+            true
+        })
+        .await
     {
-        database(
-            DatabaseOption::SET,
-            &vec![format!("game_update_{game_id}").as_str(), "true"],
-            &mut rds,
-        )
+        Ok(game_state) => game_state,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Custom(
+                Status::ServiceUnavailable,
+                format!("fire: game {game_id}: {error}"),
+            ));
+        }
+    };
+    let outcomes: Vec<FireOutcome> = match result {
+        Ok(outcomes) => outcomes,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Custom(
+                fire_error_status(&error),
+                format!("fire: game {game_id}: {error}"),
+            ));
+        }
+    };
+    for (target, outcome) in fire_position.targets.iter().zip(outcomes.iter()) {
+        println!(
+            "Game {game_id}: {} fired on {}'s ({}, {}): {}",
+            game_state.player_tags[fire_position.from],
+            game_state.player_tags[target.to],
+            target.lon,
+            target.lat,
+            outcome
+        );
+    }
+    report_bots(&game_state, game_id, bot_shots);
+    finish_game(&game_state, game_id, store).await;
+    store.notify(Channel::Game(game_id as u64)).await.unwrap();
+    Ok(Json(outcomes))
+}
+
+// The log reveals every fleet, so it is only published once the game is over
+async fn finished_game_events(
+    game_id: u32,
+    store: &SharedStore,
+) -> Result<Vec<GameEvent>, Custom<String>> {
+    let game_state: Game = match store.get_game(game_id as u64).await {
+        Ok(game_state) => game_state,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Custom(
+                Status::NotFound,
+                format!("finished_game_events: game {game_id} does not exist"),
+            ));
+        }
+    };
+    if !game_state.is_finished() {
+        return Err(Custom(
+            Status::Forbidden,
+            format!("finished_game_events: game {game_id} is still being played"),
+        ));
+    }
+    let events: Vec<GameEvent> = store
+        .events(game_id as u64)
         .await
-        .unwrap();
-        game_state.shot_list = 0;
+        .map_err(|error: String| Custom(Status::InternalServerError, error))?;
+    if events.is_empty() {
+        return Err(Custom(
+            Status::NotFound,
+            format!("finished_game_events: game {game_id} predates the event log"),
+        ));
     }
-    println!("branch 4: {:b}", game_state.shot_list);
-    json_database(
-        DatabaseOption::SET,
-        &vec![
-            game_tag,
-            ".".to_string(),
-            serde_json::to_string(&game_state).unwrap(),
-        ],
-        &mut rds,
-    )
-    .await
-    .unwrap();
-    Json(true)
+    // A log that no longer replays to the stored game is not worth serving
+    match Game::replay(&events) {
+        Ok(replayed) if replayed.phase == game_state.phase => Ok(events),
+        Ok(_) => Err(Custom(
+            Status::InternalServerError,
+            format!("finished_game_events: game {game_id}: log does not match the stored game"),
+        )),
+        Err(error) => Err(Custom(Status::InternalServerError, error)),
+    }
+}
+
+#[get("/events/<game_id>")]
+async fn get_game_events(
+    store: &State<SharedStore>,
+    game_id: u32,
+) -> Result<Json<Vec<GameEvent>>, Custom<String>> {
+    Ok(Json(finished_game_events(game_id, store).await?))
+}
+
+#[get("/record/<game_id>")]
+async fn get_game_record(
+    store: &State<SharedStore>,
+    game_id: u32,
+) -> Result<String, Custom<String>> {
+    notation::write(&finished_game_events(game_id, store).await?)
+        .map_err(|error: String| Custom(Status::InternalServerError, error))
+}
+
+// Imported records are only checked and replayed for viewing, never stored
+#[post("/import", data = "<record>")]
+async fn import_game_record(record: String) -> Result<Json<Vec<GameEvent>>, Custom<String>> {
+    let events: Vec<GameEvent> =
+        notation::read(&record).map_err(|error: String| Custom(Status::BadRequest, error))?;
+    Game::replay(&events).map_err(|error: String| Custom(Status::UnprocessableEntity, error))?;
+    Ok(Json(events))
 }
 
 #[get("/<path..>")]
@@ -504,19 +709,13 @@ async fn main_files(path: PathBuf) -> Result<NamedFile, NotFound<String>> {
 
 #[launch]
 fn rocket() -> _ {
-    rocket::build()
+    let rocket = rocket::build()
         .attach(AdHoc::on_ignite("Compiling and Configuring", |rocket| {
             Box::pin(async {
                 start::build(vec![MAIN_DIR, BOARD_DIR]);
                 rocket
             })
         }))
-        .attach(RedisDatabase::init())
-        .attach(AdHoc::on_shutdown("Stopping Docker", |_| {
-            Box::pin(async {
-                start::stop_rocket_database();
-            })
-        }))
         .mount(
             "/",
             routes![
@@ -530,8 +729,47 @@ fn rocket() -> _ {
         .mount("/main", routes![get_page_stream, main_page, main_files])
         .mount(
             "/game",
-            routes![get_game_stream, fire, process_game_request, get_game_state],
+            routes![
+                get_game_stream,
+                fire,
+                place_fleet,
+                process_game_request,
+                get_game_state,
+                get_game_events,
+                get_game_record,
+                import_game_record
+            ],
         )
         .mount("/board", routes![fire, board_files])
-        .mount("/extra_files", routes![extra_files])
+        .mount("/extra_files", routes![extra_files]);
+    // `ROCKET_STORE=memory` runs the server without Docker or Redis, losing
+    // every game when it stops
+    match rocket.figment().extract_inner::<String>("store") {
+        Ok(store) if store.eq("memory") => {
+            let store: SharedStore = Arc::new(MemoryStore::new());
+            rocket.manage(store)
+        }
+        _ => rocket
+            .attach(AdHoc::on_ignite("Starting Docker", |rocket| {
+                Box::pin(async {
+                    start::set_rocket_database();
+                    rocket
+                })
+            }))
+            .attach(RedisDatabase::init())
+            .attach(AdHoc::try_on_ignite("Connecting Store", |rocket| {
+                Box::pin(async {
+                    let store: SharedStore = match RedisDatabase::fetch(&rocket) {
+                        Some(redis) => Arc::new(RedisStore::from_database(redis)),
+                        None => return Err(rocket),
+                    };
+                    Ok(rocket.manage(store))
+                })
+            }))
+            .attach(AdHoc::on_shutdown("Stopping Docker", |_| {
+                Box::pin(async {
+                    start::stop_rocket_database();
+                })
+            })),
+    }
 }
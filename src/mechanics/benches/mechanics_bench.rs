@@ -0,0 +1,109 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use mechanics::board::{Board, PositionVectors};
+use mechanics::game::Game;
+use mechanics::rules::GameRules;
+use mechanics::ship::Ship;
+
+const PLAYER_COUNTS: [usize; 2] = [2, 8];
+
+fn started_board(players: usize) -> Board {
+    let mut board: Board = Board::new(players, GameRules::default()).unwrap();
+    for index in 0..players {
+        board.place_fleet(index, Ship::new_ships()).unwrap();
+    }
+    board.start_board();
+    board
+}
+
+fn started_game(players: usize) -> Game {
+    let mut game: Game = Game::new(players, 1, GameRules::default()).unwrap();
+    for index in 0..players {
+        game.join(format!("player_{index}")).unwrap();
+    }
+    for index in 0..players {
+        game.place_fleet(index, Ship::new_ships()).unwrap();
+    }
+    game.start("challenge".to_string()).unwrap();
+    for index in 0..players {
+        game.fire(index, (index + 1) % players, index, index)
+            .unwrap();
+    }
+    game.advance_round();
+    game
+}
+
+fn board_new(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Board::new");
+    for players in PLAYER_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(players),
+            &players,
+            |b, players| b.iter(|| Board::new(black_box(*players), GameRules::default()).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn board_start_board(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Board::start_board");
+    for players in PLAYER_COUNTS {
+        let mut board: Board = Board::new(players, GameRules::default()).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(players), &players, |b, _| {
+            b.iter(|| board.start_board())
+        });
+    }
+    group.finish();
+}
+
+fn board_fire(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Board::fire");
+    for players in PLAYER_COUNTS {
+        let board: Board = started_board(players);
+        // Each iteration fires on a fresh board so every shot lands on an untouched cell
+        group.bench_with_input(BenchmarkId::from_parameter(players), &players, |b, _| {
+            b.iter_batched_ref(
+                || board.clone(),
+                |board: &mut Board| board.fire(black_box(0), black_box(4), black_box(1)),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn board_get_board_with_player_positions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Board::get_board_with_player_positions");
+    for players in PLAYER_COUNTS {
+        let board: Board = started_board(players);
+        group.bench_with_input(BenchmarkId::from_parameter(players), &players, |b, _| {
+            b.iter(|| -> PositionVectors {
+                board.get_board_with_player_positions(black_box(players - 1))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn game_json_round_trip(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Game JSON round trip");
+    for players in PLAYER_COUNTS {
+        let game: Game = started_game(players);
+        group.bench_with_input(BenchmarkId::from_parameter(players), &players, |b, _| {
+            b.iter(|| {
+                let serialized: String = String::from(black_box(&game));
+                serde_json::from_str::<Game>(&serialized).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    board_new,
+    board_start_board,
+    board_fire,
+    board_get_board_with_player_positions,
+    game_json_round_trip
+);
+criterion_main!(benches);
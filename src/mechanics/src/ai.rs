@@ -0,0 +1,332 @@
+use crate::board::Board;
+use crate::position::FiredState;
+use crate::ship::ShipKind;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Hits inside a placement make it far more likely than one in open water
+const HIT_WEIGHT: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub fn strategy(&self) -> Box<dyn Strategy> {
+        match self {
+            Self::Easy => Box::new(RandomFire),
+            Self::Medium => Box::new(HuntTarget),
+            Self::Hard => Box::new(Density),
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CellView {
+    Unknown,
+    Miss,
+    Hit,
+    Sunk(ShipKind),
+}
+
+// What a shooter knows about one opponent: the shots fired so far and the
+// fleet the rules say is hidden somewhere on the board
+#[derive(Clone, PartialEq, Debug)]
+pub struct TargetView {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<CellView>,
+    pub fleet: Vec<(ShipKind, usize)>,
+}
+
+impl TargetView {
+    pub fn new(width: usize, height: usize, fleet: Vec<(ShipKind, usize)>) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CellView::Unknown; width * height],
+            fleet,
+        }
+    }
+
+    pub fn from_board(board: &Board, to: usize) -> Self {
+        let mut view: Self = Self::new(
+            board.rules.width,
+            board.rules.height,
+            board.rules.fleet.clone(),
+        );
+        for (x_pos, column) in board.positions.iter().enumerate() {
+            for (y_pos, position) in column.iter().enumerate() {
+                let cell: CellView = match position.fired_state.get(to) {
+                    Some(FiredState::Miss) => CellView::Miss,
+                    Some(FiredState::Hit) => CellView::Hit,
+                    Some(FiredState::Sunk(kind)) => CellView::Sunk(*kind),
+                    _ => CellView::Unknown,
+                };
+                view.set(x_pos, y_pos, cell);
+            }
+        }
+        view
+    }
+
+    pub fn get(&self, lon: usize, lat: usize) -> Option<CellView> {
+        if self.width <= lon || self.height <= lat {
+            return None;
+        }
+        Some(self.cells[lon * self.height + lat])
+    }
+
+    pub fn set(&mut self, lon: usize, lat: usize, cell: CellView) {
+        self.cells[lon * self.height + lat] = cell;
+    }
+
+    // validate_fleet keeps ships of the same kind to one length, so every full
+    // set of sunk cells of a kind accounts for one ship of it
+    pub fn afloat(&self) -> Vec<(ShipKind, usize)> {
+        let mut sunk: Vec<(ShipKind, usize)> = self
+            .fleet
+            .iter()
+            .map(|(kind, size)| {
+                let cells: usize = self
+                    .cells
+                    .iter()
+                    .filter(|cell: &&CellView| **cell == CellView::Sunk(*kind))
+                    .count();
+                (*kind, cells / (*size).max(1))
+            })
+            .collect::<Vec<(ShipKind, usize)>>();
+        self.fleet
+            .iter()
+            .filter(
+                |(kind, _)| match sunk.iter_mut().find(|(sunk_kind, _)| sunk_kind == kind) {
+                    Some((_, left)) if 0 < *left => {
+                        *left -= 1;
+                        false
+                    }
+                    _ => true,
+                },
+            )
+            .copied()
+            .collect::<Vec<(ShipKind, usize)>>()
+    }
+
+    pub fn cells_where(&self, cell: CellView) -> Vec<(usize, usize)> {
+        (0..self.width)
+            .flat_map(|x_pos: usize| (0..self.height).map(move |y_pos: usize| (x_pos, y_pos)))
+            .filter(|(x_pos, y_pos)| self.get(*x_pos, *y_pos) == Some(cell))
+            .collect::<Vec<(usize, usize)>>()
+    }
+
+    pub fn offset(
+        &self,
+        lon: usize,
+        lat: usize,
+        x_step: isize,
+        y_step: isize,
+    ) -> Option<(usize, usize)> {
+        let x_pos: usize = lon.checked_add_signed(x_step)?;
+        let y_pos: usize = lat.checked_add_signed(y_step)?;
+        self.get(x_pos, y_pos).map(|_| (x_pos, y_pos))
+    }
+
+    pub fn neighbours(&self, lon: usize, lat: usize) -> Vec<(usize, usize)> {
+        [(0, 1), (0, -1), (1, 0), (-1, 0)]
+            .into_iter()
+            .filter_map(|(x_step, y_step)| self.offset(lon, lat, x_step, y_step))
+            .collect::<Vec<(usize, usize)>>()
+    }
+}
+
+pub trait Strategy {
+    fn choose_target(&self, view: &TargetView, rng: &mut dyn RngCore) -> Option<(usize, usize)>;
+}
+
+// Searches a checkerboard until something is hit, then works outwards from
+// the hits, following the line once two of them touch
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HuntTarget;
+
+impl HuntTarget {
+    fn hunt(view: &TargetView) -> Vec<(usize, usize)> {
+        let unknown: Vec<(usize, usize)> = view.cells_where(CellView::Unknown);
+        let parity: Vec<(usize, usize)> = unknown
+            .iter()
+            .copied()
+            .filter(|(x_pos, y_pos)| (x_pos + y_pos) % 2 == 0)
+            .collect::<Vec<(usize, usize)>>();
+        if parity.is_empty() {
+            unknown
+        } else {
+            parity
+        }
+    }
+
+    fn target(view: &TargetView) -> Vec<(usize, usize)> {
+        let hits: Vec<(usize, usize)> = view.cells_where(CellView::Hit);
+        let is_hit = |cell: Option<(usize, usize)>| -> bool {
+            cell.and_then(|(x_pos, y_pos)| view.get(x_pos, y_pos)) == Some(CellView::Hit)
+        };
+        let mut along_axis: Vec<(usize, usize)> = Vec::new();
+        for (lon, lat) in hits.iter().copied() {
+            for (x_step, y_step) in [(1, 0), (0, 1)] {
+                if !is_hit(view.offset(lon, lat, x_step, y_step))
+                    && !is_hit(view.offset(lon, lat, -x_step, -y_step))
+                {
+                    continue;
+                }
+                // Walk to both ends of the run of hits and aim just past them
+                for direction in [1, -1] {
+                    let mut cell: (usize, usize) = (lon, lat);
+                    while let Some(next) =
+                        view.offset(cell.0, cell.1, direction * x_step, direction * y_step)
+                    {
+                        match view.get(next.0, next.1) {
+                            Some(CellView::Hit) => cell = next,
+                            Some(CellView::Unknown) => {
+                                along_axis.push(next);
+                                break;
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+            }
+        }
+        let mut targets: Vec<(usize, usize)> = if along_axis.is_empty() {
+            hits.iter()
+                .flat_map(|(lon, lat)| view.neighbours(*lon, *lat))
+                .filter(|(x_pos, y_pos)| view.get(*x_pos, *y_pos) == Some(CellView::Unknown))
+                .collect::<Vec<(usize, usize)>>()
+        } else {
+            along_axis
+        };
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }
+}
+
+impl Strategy for HuntTarget {
+    fn choose_target(&self, view: &TargetView, rng: &mut dyn RngCore) -> Option<(usize, usize)> {
+        let targets: Vec<(usize, usize)> = Self::target(view);
+        let candidates: Vec<(usize, usize)> = if targets.is_empty() {
+            Self::hunt(view)
+        } else {
+            targets
+        };
+        candidates.choose(rng).copied()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomFire;
+
+impl Strategy for RandomFire {
+    fn choose_target(&self, view: &TargetView, rng: &mut dyn RngCore) -> Option<(usize, usize)> {
+        view.cells_where(CellView::Unknown).choose(rng).copied()
+    }
+}
+
+// Counts, for every unknown cell, how many legal placements of the ships still
+// afloat would cover it, and fires at the most crowded one. While a ship is
+// hit but not sunk only placements through those hits are counted
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Density;
+
+impl Density {
+    pub fn density(view: &TargetView) -> Vec<usize> {
+        let hunting: bool = view.cells_where(CellView::Hit).is_empty();
+        let mut density: Vec<usize> = vec![0; view.cells.len()];
+        for (_, size) in view.afloat().into_iter() {
+            let steps: &[(isize, isize)] = if size <= 1 {
+                &[(1, 0)]
+            } else {
+                &[(1, 0), (0, 1)]
+            };
+            for lon in 0..view.width {
+                for lat in 0..view.height {
+                    for (x_step, y_step) in steps.iter().copied() {
+                        let cells: Option<Vec<(usize, usize)>> = (0..size as isize)
+                            .map(|index: isize| {
+                                view.offset(lon, lat, index * x_step, index * y_step)
+                            })
+                            .collect::<Option<Vec<(usize, usize)>>>();
+                        let cells: Vec<(usize, usize)> = match cells {
+                            Some(cells) => cells,
+                            None => continue,
+                        };
+                        let mut hits: usize = 0;
+                        let mut legal: bool = true;
+                        for (x_pos, y_pos) in cells.iter() {
+                            match view.get(*x_pos, *y_pos) {
+                                Some(CellView::Hit) => hits += 1,
+                                Some(CellView::Unknown) => (),
+                                _ => legal = false,
+                            }
+                        }
+                        if !legal || (!hunting && hits == 0) {
+                            continue;
+                        }
+                        // A long ship with many hits would overflow, and any
+                        // placement that reaches the ceiling is a sure thing anyway
+                        let weight: usize = if hunting {
+                            1
+                        } else {
+                            HIT_WEIGHT.saturating_pow(hits as u32)
+                        };
+                        for (x_pos, y_pos) in cells.into_iter() {
+                            if view.get(x_pos, y_pos) == Some(CellView::Unknown) {
+                                let cell: &mut usize = &mut density[x_pos * view.height + y_pos];
+                                *cell = cell.saturating_add(weight);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        density
+    }
+}
+
+impl Strategy for Density {
+    fn choose_target(&self, view: &TargetView, rng: &mut dyn RngCore) -> Option<(usize, usize)> {
+        let density: Vec<usize> = Self::density(view);
+        let unknown: Vec<(usize, usize)> = view.cells_where(CellView::Unknown);
+        let best: usize = unknown
+            .iter()
+            .map(|(x_pos, y_pos)| density[x_pos * view.height + y_pos])
+            .max()?;
+        // Nothing fits the open hits or the afloat fleet, so any unknown cell will do
+        if best == 0 {
+            return HuntTarget.choose_target(view, rng);
+        }
+        unknown
+            .into_iter()
+            .filter(|(x_pos, y_pos)| density[x_pos * view.height + y_pos] == best)
+            .collect::<Vec<(usize, usize)>>()
+            .choose(rng)
+            .copied()
+    }
+}
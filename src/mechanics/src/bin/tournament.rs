@@ -0,0 +1,295 @@
+use mechanics::ai::{Difficulty, Strategy};
+use mechanics::event::GameEvent;
+use mechanics::game::{Game, GamePhase};
+use mechanics::position::Target;
+use mechanics::rules::{GameRules, TurnMode};
+use mechanics::ship::FleetPreset;
+use std::env;
+use std::process::ExitCode;
+
+// 95% two-sided normal quantile
+const Z_95: f64 = 1.96;
+
+const USAGE: &str = "Usage: tournament [options]
+    --games N          games to play (default 1000)
+    --seed N           seed of the first game, each game after adds one (default 0)
+    --strategies A,B   one difficulty per seat: easy, medium or hard (default easy,medium,hard)
+    --preset NAME      fleet preset: classic1990, classic1967 or russian (default classic1990)
+    --size WxH         board size (default 10x10)
+    --salvo            one shot per surviving ship each round
+    --turns MODE       simultaneous, alternating or chain (default simultaneous)
+    --verify           replay every finished game from its event log";
+
+struct Settings {
+    games: u64,
+    seed: u64,
+    seats: Vec<Difficulty>,
+    rules: GameRules,
+    verify: bool,
+}
+
+#[derive(Default)]
+struct Record {
+    games: u64,
+    wins: u64,
+    ties: u64,
+    // Shots fired by the seat in each game it won outright
+    shots_to_win: Vec<f64>,
+}
+
+struct Finished {
+    winners: Vec<usize>,
+    shots: Vec<usize>,
+}
+
+fn parse_settings(mut args: impl Iterator<Item = String>) -> Result<Settings, String> {
+    let mut games: u64 = 1000;
+    let mut seed: u64 = 0;
+    let mut seats: Vec<Difficulty> = Difficulty::ALL.to_vec();
+    let mut preset: FleetPreset = FleetPreset::default();
+    let (mut width, mut height) = (10, 10);
+    let mut salvo: bool = false;
+    let mut turn_mode: TurnMode = TurnMode::Simultaneous;
+    let mut verify: bool = false;
+    while let Some(arg) = args.next() {
+        let mut value = || -> Result<String, String> {
+            args.next()
+                .ok_or_else(|| format!("tournament: {} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--games" => games = number(&value()?)?,
+            "--seed" => seed = number(&value()?)?,
+            "--strategies" => {
+                seats = value()?
+                    .split(',')
+                    .map(|name: &str| {
+                        Difficulty::ALL
+                            .into_iter()
+                            .find(|difficulty: &Difficulty| {
+                                difficulty.display_name().eq_ignore_ascii_case(name)
+                            })
+                            .ok_or_else(|| format!("tournament: unknown strategy {}", name))
+                    })
+                    .collect::<Result<Vec<Difficulty>, String>>()?
+            }
+            "--preset" => {
+                let name: String = value()?;
+                preset = FleetPreset::ALL
+                    .into_iter()
+                    .find(|preset: &FleetPreset| {
+                        format!("{:?}", preset).eq_ignore_ascii_case(&name)
+                    })
+                    .ok_or_else(|| format!("tournament: unknown fleet preset {}", name))?
+            }
+            "--size" => {
+                let size: String = value()?;
+                let (size_x, size_y) = size
+                    .split_once('x')
+                    .ok_or_else(|| format!("tournament: size {} must be WxH", size))?;
+                (width, height) = (number(size_x)?, number(size_y)?);
+            }
+            "--salvo" => salvo = true,
+            "--turns" => {
+                turn_mode = match value()?.as_str() {
+                    "simultaneous" => TurnMode::Simultaneous,
+                    "alternating" => TurnMode::Alternating {
+                        chain_on_hit: false,
+                    },
+                    "chain" => TurnMode::Alternating { chain_on_hit: true },
+                    other => return Err(format!("tournament: unknown turn mode {}", other)),
+                }
+            }
+            "--verify" => verify = true,
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("tournament: unknown option {}\n{}", other, USAGE)),
+        }
+    }
+    let mut rules: GameRules = GameRules::with_preset(width, height, preset, seats.len().max(2))?;
+    rules.salvo = salvo;
+    rules.turn_mode = turn_mode;
+    rules.validate(seats.len())?;
+    Ok(Settings {
+        games,
+        seed,
+        seats,
+        rules,
+        verify,
+    })
+}
+
+fn number<T: std::str::FromStr>(token: &str) -> Result<T, String> {
+    token
+        .parse::<T>()
+        .map_err(|_| format!("tournament: {} is not a number", token))
+}
+
+// Every seat is a bot, so the game only stops once it is decided
+fn play(
+    settings: &Settings,
+    game_number: u64,
+    strategies: &[Box<dyn Strategy>],
+) -> Result<Finished, String> {
+    let seed: u64 = settings.seed.wrapping_add(game_number);
+    let players: usize = strategies.len();
+    let mut game: Game = Game::new_with_seed(players, game_number, settings.rules.clone(), seed)?;
+    for index in 0..players {
+        game.join_bot(format!("bot_{index}"))?;
+    }
+    game.place_bot_fleets()?;
+    let challenge: String = game.new_challenge();
+    game.start(challenge)?;
+    let mut shots: Vec<usize> = vec![0; players];
+    while !game.is_finished() {
+        let mut fired: bool = false;
+        for (index, strategy) in strategies.iter().enumerate() {
+            if game.shots_remaining(index) == 0 {
+                continue;
+            }
+            let targets: Vec<Target> = game.bot_salvo_with(index, strategy.as_ref());
+            game.fire_salvo(index, &targets)
+                .map_err(|error| format!("game {} (seed {}): {}", game_number, seed, error))?;
+            shots[index] += targets.len();
+            fired = true;
+        }
+        let advanced: bool = game.round_complete() && game.advance_round();
+        if !fired && !advanced {
+            return Err(format!(
+                "game {} (seed {}): stalled in {}",
+                game_number, seed, game.phase
+            ));
+        }
+    }
+    if settings.verify {
+        let events: Vec<GameEvent> = game.take_events();
+        let replayed: Game = Game::replay(&events)
+            .map_err(|error: String| format!("game {} (seed {}): {}", game_number, seed, error))?;
+        if replayed.phase != game.phase {
+            return Err(format!(
+                "game {} (seed {}): replay finished as {} instead of {}",
+                game_number, seed, replayed.phase, game.phase
+            ));
+        }
+    }
+    match game.phase {
+        GamePhase::Finished { winners } => Ok(Finished { winners, shots }),
+        _ => unreachable!(),
+    }
+}
+
+// Wilson score interval, which stays inside [0, 1] for small samples
+fn wilson(wins: u64, games: u64) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 0.0);
+    }
+    let (wins, games) = (wins as f64, games as f64);
+    let rate: f64 = wins / games;
+    let denominator: f64 = 1.0 + Z_95 * Z_95 / games;
+    let centre: f64 = rate + Z_95 * Z_95 / (2.0 * games);
+    let spread: f64 =
+        Z_95 * (rate * (1.0 - rate) / games + Z_95 * Z_95 / (4.0 * games * games)).sqrt();
+    (
+        (centre - spread) / denominator,
+        (centre + spread) / denominator,
+    )
+}
+
+fn mean_interval(samples: &[f64]) -> Option<(f64, f64)> {
+    if samples.is_empty() {
+        return None;
+    }
+    let count: f64 = samples.len() as f64;
+    let mean: f64 = samples.iter().sum::<f64>() / count;
+    if samples.len() == 1 {
+        return Some((mean, 0.0));
+    }
+    let variance: f64 = samples
+        .iter()
+        .map(|sample: &f64| (sample - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+    Some((mean, Z_95 * (variance / count).sqrt()))
+}
+
+fn main() -> ExitCode {
+    let settings: Settings = match parse_settings(env::args().skip(1)) {
+        Ok(settings) => settings,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let seats: usize = settings.seats.len();
+    let mut records: Vec<(Difficulty, Record)> = Vec::new();
+    for difficulty in settings.seats.iter() {
+        if !records.iter().any(|(seen, _)| seen == difficulty) {
+            records.push((*difficulty, Record::default()));
+        }
+    }
+    for game_number in 0..settings.games {
+        // Seats rotate every game so no strategy keeps the first move
+        let lineup: Vec<Difficulty> = (0..seats)
+            .map(|seat: usize| settings.seats[(seat + game_number as usize) % seats])
+            .collect::<Vec<Difficulty>>();
+        let strategies: Vec<Box<dyn Strategy>> = lineup
+            .iter()
+            .map(|difficulty: &Difficulty| difficulty.strategy())
+            .collect::<Vec<Box<dyn Strategy>>>();
+        let finished: Finished = match play(&settings, game_number, &strategies) {
+            Ok(finished) => finished,
+            Err(error) => {
+                eprintln!("tournament: {}", error);
+                return ExitCode::FAILURE;
+            }
+        };
+        for (seat, difficulty) in lineup.iter().enumerate() {
+            let record: &mut Record = &mut records
+                .iter_mut()
+                .find(|(seen, _)| seen == difficulty)
+                .unwrap()
+                .1;
+            record.games += 1;
+            if !finished.winners.contains(&seat) {
+                continue;
+            }
+            if finished.winners.len() == 1 {
+                record.wins += 1;
+                record.shots_to_win.push(finished.shots[seat] as f64);
+            } else {
+                record.ties += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} games, {} seats, {}x{} board, seeds {}..{}",
+        settings.games,
+        seats,
+        settings.rules.width,
+        settings.rules.height,
+        settings.seed,
+        settings.seed.wrapping_add(settings.games)
+    );
+    println!(
+        "{:<10} {:>7} {:>7} {:>6} {:>9} {:>17} {:>18}",
+        "Strategy", "Games", "Wins", "Ties", "Win rate", "95% CI", "Shots to win"
+    );
+    for (difficulty, record) in records.iter() {
+        let (low, high) = wilson(record.wins, record.games);
+        let shots: String = match mean_interval(&record.shots_to_win) {
+            Some((mean, spread)) => format!("{:.1} ± {:.1}", mean, spread),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<10} {:>7} {:>7} {:>6} {:>8.1}% {:>7.1}% - {:>5.1}% {:>18}",
+            difficulty.display_name(),
+            record.games,
+            record.wins,
+            record.ties,
+            100.0 * record.wins as f64 / record.games.max(1) as f64,
+            100.0 * low,
+            100.0 * high,
+            shots
+        );
+    }
+    ExitCode::SUCCESS
+}
@@ -1,54 +1,290 @@
-use crate::position::{FiredState, Position};
-use crate::ship::{Ship, ShipSet};
-use serde::{Deserialize, Serialize};
+use crate::position::{FireError, FireOutcome, FiredState, Position};
+use crate::rules::GameRules;
+use crate::ship::{Ship, ShipKind, ShipSet};
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-const BOARD_SIZE: usize = 10;
 pub type PositionVectors = Vec<Vec<Position>>;
 
-#[derive(Serialize, Deserialize)]
+const WORD_BITS: usize = u32::BITS as usize;
+
+// Words stay u32 so every value survives a round trip through JSON numbers
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BitLayer(Vec<u32>);
+
+impl BitLayer {
+    pub fn new(cells: usize) -> Self {
+        Self(vec![0; cells.div_ceil(WORD_BITS)])
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.0
+            .get(index / WORD_BITS)
+            .map(|word: &u32| word & (1 << (index % WORD_BITS)) != 0)
+            .unwrap_or(false)
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    pub fn count(&self) -> usize {
+        self.0
+            .iter()
+            .map(|word: &u32| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self(
+            (0..self.0.len().max(other.0.len()))
+                .map(|index: usize| {
+                    self.0.get(index).copied().unwrap_or(0)
+                        | other.0.get(index).copied().unwrap_or(0)
+                })
+                .collect::<Vec<u32>>(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(index, word): (usize, &u32)| {
+                (0..WORD_BITS)
+                    .filter(move |bit: &usize| word & (1 << bit) != 0)
+                    .map(move |bit: usize| index * WORD_BITS + bit)
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct PlayerBits {
+    pub empty: BitLayer,
+    pub hits: BitLayer,
+    pub misses: BitLayer,
+    pub ships: Vec<(ShipKind, BitLayer)>,
+    pub sunk: Vec<(ShipKind, BitLayer)>,
+}
+
+impl PlayerBits {
+    fn new(cells: usize) -> Self {
+        Self {
+            empty: BitLayer::new(cells),
+            hits: BitLayer::new(cells),
+            misses: BitLayer::new(cells),
+            ships: Vec::new(),
+            sunk: Vec::new(),
+        }
+    }
+
+    fn kind_layer(
+        layers: &mut Vec<(ShipKind, BitLayer)>,
+        kind: ShipKind,
+        cells: usize,
+    ) -> &mut BitLayer {
+        let index: usize = match layers
+            .iter()
+            .position(|(layer_kind, _)| *layer_kind == kind)
+        {
+            Some(index) => index,
+            None => {
+                layers.push((kind, BitLayer::new(cells)));
+                layers.len() - 1
+            }
+        };
+        &mut layers[index].1
+    }
+
+    // Every cell that has been shot at, whatever the result
+    pub fn fired(&self) -> BitLayer {
+        self.sunk.iter().fold(
+            self.hits.union(&self.misses),
+            |fired: BitLayer, (_, layer)| fired.union(layer),
+        )
+    }
+
+    pub fn state(&self, index: usize) -> FiredState {
+        if self.hits.get(index) {
+            FiredState::Hit
+        } else if self.misses.get(index) {
+            FiredState::Miss
+        } else if self.empty.get(index) {
+            FiredState::Empty
+        } else if let Some((kind, _)) = self.sunk.iter().find(|(_, layer)| layer.get(index)) {
+            FiredState::Sunk(*kind)
+        } else if let Some((kind, _)) = self.ships.iter().find(|(_, layer)| layer.get(index)) {
+            FiredState::Ship(*kind)
+        } else {
+            FiredState::Untouched
+        }
+    }
+}
+
+// One set of bitmasks per player instead of a FiredState per player per cell
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CompactBoard {
+    pub width: usize,
+    pub height: usize,
+    pub players: Vec<PlayerBits>,
+}
+
+impl CompactBoard {
+    pub fn cell_index(&self, lon: usize, lat: usize) -> usize {
+        lon * self.height + lat
+    }
+
+    pub fn cell_position(&self, index: usize) -> (usize, usize) {
+        (index / self.height, index % self.height)
+    }
+
+    pub fn state(&self, player_index: usize, lon: usize, lat: usize) -> FiredState {
+        self.players[player_index].state(self.cell_index(lon, lat))
+    }
+
+    pub fn from_positions(positions: &PositionVectors) -> Self {
+        let width: usize = positions.len();
+        let height: usize = positions.first().map(|column| column.len()).unwrap_or(0);
+        let players: usize = positions
+            .first()
+            .and_then(|column| column.first())
+            .map(|position: &Position| position.fired_state.len())
+            .unwrap_or(0);
+        let cells: usize = width * height;
+        let mut compact: Self = Self {
+            width,
+            height,
+            players: vec![PlayerBits::new(cells); players],
+        };
+        for (x_index, column) in positions.iter().enumerate() {
+            for (y_index, position) in column.iter().enumerate() {
+                let index: usize = compact.cell_index(x_index, y_index);
+                for (player_bits, fired_state) in
+                    compact.players.iter_mut().zip(position.fired_state.iter())
+                {
+                    match fired_state {
+                        FiredState::Hit => player_bits.hits.set(index),
+                        FiredState::Miss => player_bits.misses.set(index),
+                        FiredState::Empty => player_bits.empty.set(index),
+                        FiredState::Untouched => (),
+                        FiredState::Ship(kind) => {
+                            PlayerBits::kind_layer(&mut player_bits.ships, *kind, cells).set(index)
+                        }
+                        FiredState::Sunk(kind) => {
+                            PlayerBits::kind_layer(&mut player_bits.sunk, *kind, cells).set(index)
+                        }
+                    }
+                }
+            }
+        }
+        compact
+    }
+
+    pub fn to_positions(&self) -> PositionVectors {
+        (0..self.width)
+            .map(|x_index: usize| {
+                (0..self.height)
+                    .map(|y_index: usize| Position {
+                        fired_state: self
+                            .players
+                            .iter()
+                            .map(|player_bits: &PlayerBits| {
+                                player_bits.state(self.cell_index(x_index, y_index))
+                            })
+                            .collect::<Vec<FiredState>>(),
+                    })
+                    .collect::<Vec<Position>>()
+            })
+            .collect::<PositionVectors>()
+    }
+}
+
+// Boards are stored compactly but games saved before the switch still load
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPositions {
+    Compact(CompactBoard),
+    Legacy(PositionVectors),
+}
+
+fn serialize_positions<S: Serializer>(
+    positions: &PositionVectors,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    CompactBoard::from_positions(positions).serialize(serializer)
+}
+
+fn deserialize_positions<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<PositionVectors, D::Error> {
+    Ok(match StoredPositions::deserialize(deserializer)? {
+        StoredPositions::Compact(compact) => compact.to_positions(),
+        StoredPositions::Legacy(positions) => positions,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Board {
+    #[serde(
+        serialize_with = "serialize_positions",
+        deserialize_with = "deserialize_positions"
+    )]
     pub positions: PositionVectors,
     pub ship_set: Vec<Vec<Ship>>,
     players: usize,
+    #[serde(default)]
+    pub rules: GameRules,
 }
 
 impl Board {
-    pub fn new(players: usize) -> Self {
-        Self {
-            positions: Self::initialize_board(players),
-            ship_set: ShipSet::new(players),
+    pub fn new(players: usize, rules: GameRules) -> Result<Self, String> {
+        Self::new_with_rng(players, rules, &mut rand::thread_rng())
+    }
+
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        players: usize,
+        rules: GameRules,
+        rng: &mut R,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            positions: Self::initialize_board(players, &rules),
+            ship_set: ShipSet::new_with_rng(players, &rules, rng)?,
             players,
-        }
+            rules,
+        })
     }
 
-    pub fn initialize_board(players: usize) -> PositionVectors {
-        (0..BOARD_SIZE)
+    pub fn initialize_board(players: usize, rules: &GameRules) -> PositionVectors {
+        (0..rules.width)
             .map(|index| {
-                (0..BOARD_SIZE)
-                    .map(|jndex| Position::new(index, jndex, None, players).unwrap())
+                (0..rules.height)
+                    .map(|jndex| Position::new(index, jndex, None, players, rules).unwrap())
                     .collect::<Vec<Position>>()
             })
             .collect::<PositionVectors>()
     }
 
     pub fn empty() -> Self {
+        let rules: GameRules = GameRules::default();
         Self {
-            positions: Self::initialize_board(0),
-            ship_set: ShipSet::new(0),
+            positions: Self::initialize_board(0, &rules),
+            ship_set: Vec::new(),
             players: 0,
+            rules,
         }
     }
 
     pub fn start_board(&mut self) {
-        self.positions = (0..BOARD_SIZE)
+        self.positions = (0..self.rules.width)
             .map(|x_index: usize| {
-                (0..BOARD_SIZE)
+                (0..self.rules.height)
                     .map(|y_index: usize| {
                         Position::new(
                             x_index,
                             y_index,
                             Some(vec![FiredState::Untouched; self.players]),
                             self.players,
+                            &self.rules,
                         )
                         .unwrap()
                     })
@@ -58,53 +294,109 @@ impl Board {
     }
 
     pub fn get_board_with_player_positions(&self, player_index: usize) -> PositionVectors {
-        let player_ships: Vec<(usize, usize, String)> = self.ship_set[player_index]
+        let player_ships: Vec<(usize, usize, ShipKind)> = self.ship_set[player_index]
             .iter()
             .map(|ship: &Ship| {
                 ship.location
                     .clone()
                     .into_iter()
-                    .map(|(x_pos, y_pos): (usize, usize)| (x_pos, y_pos, ship.name.clone()))
-                    .collect::<Vec<(usize, usize, String)>>()
+                    .map(|(x_pos, y_pos): (usize, usize)| (x_pos, y_pos, ship.kind))
+                    .collect::<Vec<(usize, usize, ShipKind)>>()
             })
-            .collect::<Vec<Vec<(usize, usize, String)>>>()
+            .collect::<Vec<Vec<(usize, usize, ShipKind)>>>()
             .into_iter()
             .flatten()
-            .collect::<Vec<(usize, usize, String)>>();
+            .collect::<Vec<(usize, usize, ShipKind)>>();
         let mut player_personal_board: PositionVectors = self.positions.clone();
-        for (x_index, y_index, ship_name) in player_ships.iter() {
-            if player_personal_board[*x_index][*y_index].fired_state[player_index]
-                != FiredState::Hit
-            {
+        for (x_index, y_index, ship_kind) in player_ships.iter() {
+            if !matches!(
+                player_personal_board[*x_index][*y_index].fired_state[player_index],
+                FiredState::Hit | FiredState::Sunk(_)
+            ) {
                 player_personal_board[*x_index][*y_index].fired_state[player_index] =
-                    FiredState::Ship(ship_name.to_string());
+                    FiredState::Ship(*ship_kind);
             }
         }
         player_personal_board
     }
 
-    pub fn fire(&mut self, lon: usize, lat: usize, to: usize) -> Result<PositionVectors, String> {
-        let mut new_fired_state: Vec<FiredState> = self.positions[lon][lat].get_fired_state();
-        if to > new_fired_state.len() - 1 as usize {
+    pub fn place_fleet(&mut self, player_index: usize, ships: Vec<Ship>) -> Result<(), String> {
+        if self.players <= player_index {
             return Err(format!(
-                "board: fire: {} is greater than the length of the player list index ({})",
-                to,
-                new_fired_state.len() - 1
+                "board: place_fleet: {} is greater than the length of the player list index ({})",
+                player_index,
+                self.players - 1
             ));
         }
-        let mut new_positions = self.positions.clone();
-        new_fired_state[to] = if self.ship_set[to]
-            .clone()
-            .into_iter()
-            .fold(false, |acc: bool, ship: Ship| {
-                acc || ship.check_hit(lon, lat)
-            }) {
-            FiredState::Hit
-        } else {
-            FiredState::Miss
+        Ship::validate_fleet(&ships, &self.rules)?;
+        self.ship_set[player_index] = ships;
+        Ok(())
+    }
+
+    pub fn fire(&mut self, lon: usize, lat: usize, to: usize) -> Result<FireOutcome, FireError> {
+        if !self.rules.in_bounds(lon, lat) {
+            return Err(FireError::OutOfBounds { lon, lat });
+        }
+        if self.players <= to {
+            return Err(FireError::UnknownTarget(to));
+        }
+        if self.positions[lon][lat].fired_state[to] != FiredState::Untouched {
+            return Ok(FireOutcome::AlreadyFired);
+        }
+        let hit_ship: Ship = match self.ship_set[to]
+            .iter()
+            .find(|ship: &&Ship| ship.check_hit(lon, lat))
+        {
+            Some(ship) => ship.clone(),
+            None => {
+                self.positions[lon][lat].fired_state[to] = FiredState::Miss;
+                return Ok(FireOutcome::Miss);
+            }
         };
-        new_positions[lon as usize][lat as usize] =
-            Position::new(lon, lat, Some(new_fired_state), 0)?;
-        Ok(new_positions)
+        self.positions[lon][lat].fired_state[to] = FiredState::Hit;
+        // Reveal the ship to the shooter once every one of its cells is hit
+        if !hit_ship
+            .location
+            .iter()
+            .all(|(x_pos, y_pos)| self.positions[*x_pos][*y_pos].fired_state[to] == FiredState::Hit)
+        {
+            return Ok(FireOutcome::Hit {
+                kind: hit_ship.kind,
+            });
+        }
+        for (x_pos, y_pos) in hit_ship.location.iter() {
+            self.positions[*x_pos][*y_pos].fired_state[to] = FiredState::Sunk(hit_ship.kind);
+        }
+        Ok(if self.fleet_sunk(to) {
+            FireOutcome::PlayerEliminated {
+                kind: hit_ship.kind,
+            }
+        } else {
+            FireOutcome::Sunk {
+                kind: hit_ship.kind,
+            }
+        })
+    }
+
+    pub fn is_sunk(&self, player_index: usize, ship: &Ship) -> bool {
+        ship.location.iter().all(|(x_pos, y_pos)| {
+            matches!(
+                self.positions[*x_pos][*y_pos].fired_state[player_index],
+                FiredState::Sunk(_)
+            )
+        })
+    }
+
+    pub fn fleet_sunk(&self, player_index: usize) -> bool {
+        self.ship_set[player_index]
+            .iter()
+            .all(|ship: &Ship| self.is_sunk(player_index, ship))
+    }
+
+    pub fn ships_afloat(&self, player_index: usize) -> usize {
+        self.ship_set[player_index]
+            .iter()
+            .filter(|ship: &&Ship| !self.is_sunk(player_index, ship))
+            .count()
     }
 }
@@ -0,0 +1,85 @@
+use crate::position::{FireOutcome, Target};
+use crate::rules::GameRules;
+use crate::ship::{Ship, ShipKind};
+use serde::{Deserialize, Serialize};
+
+// Sunk ships and eliminations are consequences of other events, so a replay
+// only checks them against what it records rather than applying them
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    GameCreated {
+        number_of_players: usize,
+        game_number: u64,
+        rules: GameRules,
+        seed: u64,
+    },
+    PlayerJoined {
+        player_id: String,
+    },
+    FleetPlaced {
+        player: usize,
+        ships: Vec<Ship>,
+    },
+    GameStarted {
+        challenge: String,
+    },
+    ShotFired {
+        from: usize,
+        shot: usize,
+        target: Target,
+        outcome: FireOutcome,
+    },
+    ShipSunk {
+        player: usize,
+        kind: ShipKind,
+    },
+    PlayerEliminated {
+        player: usize,
+    },
+    RoundAdvanced {
+        round: u32,
+        on_move: Option<usize>,
+    },
+    GameFinished {
+        winners: Vec<usize>,
+    },
+}
+
+impl std::fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GameCreated {
+                number_of_players,
+                game_number,
+                seed,
+                ..
+            } => write!(
+                f,
+                "Game {} created for {} players (seed {})",
+                game_number, number_of_players, seed
+            ),
+            Self::PlayerJoined { player_id } => write!(f, "{} joined", player_id),
+            Self::FleetPlaced { player, ships } => {
+                write!(f, "Player {} placed {} ships", player, ships.len())
+            }
+            Self::GameStarted { .. } => write!(f, "Game started"),
+            Self::ShotFired {
+                from,
+                target,
+                outcome,
+                ..
+            } => write!(
+                f,
+                "Player {} fired at player {} ({}, {}): {}",
+                from, target.to, target.lon, target.lat, outcome
+            ),
+            Self::ShipSunk { player, kind } => write!(f, "Player {}'s {} sank", player, kind),
+            Self::PlayerEliminated { player } => write!(f, "Player {} was eliminated", player),
+            Self::RoundAdvanced { round, on_move } => match on_move {
+                Some(on_move) => write!(f, "Round {}, player {} to move", round, on_move),
+                None => write!(f, "Round {}", round),
+            },
+            Self::GameFinished { winners } => write!(f, "Game finished (Winners {:?})", winners),
+        }
+    }
+}
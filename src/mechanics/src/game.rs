@@ -1,6 +1,60 @@
+use crate::ai::{CellView, Difficulty, Strategy, TargetView};
 use crate::board::Board;
+use crate::event::GameEvent;
+use crate::position::{FireError, FireOutcome, FiredState, Position, Target};
+use crate::rng::{self, RngStream};
+use crate::rules::{GameRules, TurnMode};
+use crate::ship::Ship;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use serde::{Serialize, Deserialize};
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub enum GamePhase {
+    #[default]
+    Lobby,
+    Placement,
+    InProgress {
+        round: u32,
+    },
+    Finished {
+        winners: Vec<usize>,
+    },
+}
+
+impl GamePhase {
+    pub fn can_transition_to(&self, next: &GamePhase) -> bool {
+        match (self, next) {
+            (Self::Lobby, Self::Placement) => true,
+            (Self::Placement, Self::InProgress { round }) => *round == 1,
+            (Self::InProgress { round }, Self::InProgress { round: next_round }) => {
+                *next_round == round + 1
+            }
+            (Self::InProgress { .. }, Self::Finished { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lobby => write!(f, "Lobby"),
+            Self::Placement => write!(f, "Placement"),
+            Self::InProgress { round } => write!(f, "InProgress (Round {})", round),
+            Self::Finished { winners } => write!(f, "Finished (Winners {:?})", winners),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct TurnState {
+    pub mode: TurnMode,
+    pub on_move: Option<usize>,
+    pub shots_remaining: Vec<usize>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Game {
     pub boards: Board,
@@ -8,19 +62,601 @@ pub struct Game {
     pub player_tags: Vec<String>,
     pub challenge: String,
     pub game_number: u64,
-    pub shot_list: u32,
+    #[serde(default)]
+    pub shots_remaining: Vec<usize>,
+    #[serde(default)]
+    pub on_move: Option<usize>,
+    #[serde(default)]
+    pub placement_list: u32,
+    #[serde(default)]
+    pub eliminated: u32,
+    #[serde(default)]
+    pub bots: u32,
+    #[serde(default)]
+    pub phase: GamePhase,
+    #[serde(default)]
+    pub seed: u64,
+    // How well any bot seats in this game play
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    // Events since the last take_events; the log itself lives with the store
+    #[serde(skip)]
+    events: Vec<GameEvent>,
 }
 
 impl Game {
-    pub fn new(number_of_players: usize, game_number: u64) -> Self {
-        Self {
-            boards: Board::new(number_of_players),
+    pub fn new(
+        number_of_players: usize,
+        game_number: u64,
+        rules: GameRules,
+    ) -> Result<Self, String> {
+        Self::new_with_seed(number_of_players, game_number, rules, rng::random_seed())
+    }
+
+    pub fn new_with_seed(
+        number_of_players: usize,
+        game_number: u64,
+        rules: GameRules,
+        seed: u64,
+    ) -> Result<Self, String> {
+        // Players are tracked in u32 masks and every board is allocated up front
+        rules.validate(number_of_players)?;
+        let mut placement: ChaCha8Rng = rng::game_rng(seed, RngStream::Placement);
+        let created: GameEvent = GameEvent::GameCreated {
+            number_of_players,
+            game_number,
+            rules: rules.clone(),
+            seed,
+        };
+        Ok(Self {
+            boards: Board::new_with_rng(number_of_players, rules, &mut placement)?,
             number_of_players,
             player_tags: Vec::with_capacity(number_of_players),
             challenge: String::new(),
             game_number,
-            shot_list: 0,
+            shots_remaining: vec![0; number_of_players],
+            on_move: None,
+            placement_list: 0,
+            eliminated: 0,
+            bots: 0,
+            phase: GamePhase::Lobby,
+            seed,
+            difficulty: Difficulty::default(),
+            events: vec![created],
+        })
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Rebuilds a game from its full log and checks that doing so records the
+    // exact same log, so a tampered or reordered log is rejected
+    pub fn replay(events: &[GameEvent]) -> Result<Self, String> {
+        let mut game: Self = match events.first() {
+            Some(GameEvent::GameCreated {
+                number_of_players,
+                game_number,
+                rules,
+                seed,
+            }) => Self::new_with_seed(*number_of_players, *game_number, rules.clone(), *seed)?,
+            _ => return Err("game: replay: log does not start with GameCreated".to_string()),
+        };
+        let mut salvo: Vec<Target> = Vec::new();
+        let mut shooter: usize = 0;
+        for (index, event) in events.iter().enumerate().skip(1) {
+            if let GameEvent::ShotFired {
+                from, shot, target, ..
+            } = event
+            {
+                if *shot == 0 {
+                    game.replay_salvo(shooter, &mut salvo)?;
+                    shooter = *from;
+                }
+                salvo.push(*target);
+                continue;
+            }
+            if matches!(
+                event,
+                GameEvent::ShipSunk { .. } | GameEvent::PlayerEliminated { .. }
+            ) {
+                continue;
+            }
+            game.replay_salvo(shooter, &mut salvo)?;
+            match event {
+                GameEvent::PlayerJoined { player_id } => game.join(player_id.clone())?,
+                GameEvent::FleetPlaced { player, ships } => {
+                    game.place_fleet(*player, ships.clone())?
+                }
+                GameEvent::GameStarted { challenge } => game.start(challenge.clone())?,
+                GameEvent::RoundAdvanced { .. } | GameEvent::GameFinished { .. } => {
+                    if !game.advance_round() {
+                        return Err(format!(
+                            "game: replay: event {} advances an unfinished round",
+                            index
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "game: replay: event {} ({}) cannot appear mid game",
+                        index, event
+                    ))
+                }
+            }
+        }
+        game.replay_salvo(shooter, &mut salvo)?;
+        if let Some(index) = (0..events.len().max(game.events.len()))
+            .find(|index: &usize| events.get(*index) != game.events.get(*index))
+        {
+            return Err(format!(
+                "game: replay: log diverges from the replayed game at event {}",
+                index
+            ));
+        }
+        Ok(game)
+    }
+
+    fn replay_salvo(&mut self, from: usize, salvo: &mut Vec<Target>) -> Result<(), String> {
+        if salvo.is_empty() {
+            return Ok(());
+        }
+        self.fire_salvo(from, salvo)
+            .map_err(|error: FireError| format!("game: replay: {}", error))?;
+        salvo.clear();
+        Ok(())
+    }
+
+    pub fn rng(&self, stream: RngStream) -> ChaCha8Rng {
+        rng::game_rng(self.seed, stream)
+    }
+
+    pub fn new_challenge(&self) -> String {
+        rng::challenge(&mut self.rng(RngStream::Challenge))
+    }
+
+    pub fn transition(&mut self, next: GamePhase) -> Result<(), String> {
+        if !self.phase.can_transition_to(&next) {
+            return Err(format!(
+                "game: transition: game {} cannot move from {} to {}",
+                self.game_number, self.phase, next
+            ));
+        }
+        self.phase = next;
+        Ok(())
+    }
+
+    pub fn join(&mut self, player_id: String) -> Result<(), String> {
+        if self.phase != GamePhase::Lobby {
+            return Err(format!(
+                "game: join: game {} is no longer accepting players ({})",
+                self.game_number, self.phase
+            ));
+        }
+        if self.player_tags.contains(&player_id) {
+            return Err(format!(
+                "game: join: {} has already joined game {}",
+                player_id, self.game_number
+            ));
+        }
+        self.player_tags.push(player_id.clone());
+        self.events.push(GameEvent::PlayerJoined { player_id });
+        if self.player_tags.len() == self.number_of_players {
+            self.boards.start_board();
+            self.transition(GamePhase::Placement)?;
+        }
+        Ok(())
+    }
+
+    pub fn place_fleet(&mut self, player_index: usize, ships: Vec<Ship>) -> Result<(), String> {
+        if self.phase != GamePhase::Placement {
+            return Err(format!(
+                "game: place_fleet: game {} is not accepting fleet placements",
+                self.game_number
+            ));
+        }
+        if self.number_of_players <= player_index {
+            return Err(format!(
+                "game: place_fleet: {} is not a player index of game {}",
+                player_index, self.game_number
+            ));
+        }
+        if (self.placement_list & (1 << player_index)) != 0 {
+            return Err(format!(
+                "game: place_fleet: player {} has already placed their fleet",
+                player_index
+            ));
+        }
+        self.boards.place_fleet(player_index, ships.clone())?;
+        self.placement_list |= 1 << player_index;
+        self.events.push(GameEvent::FleetPlaced {
+            player: player_index,
+            ships,
+        });
+        Ok(())
+    }
+
+    pub fn join_bot(&mut self, player_id: String) -> Result<(), String> {
+        self.join(player_id)?;
+        self.bots |= 1 << (self.player_tags.len() - 1);
+        Ok(())
+    }
+
+    pub fn is_bot(&self, player_index: usize) -> bool {
+        (self.bots & (1 << player_index)) != 0
+    }
+
+    // Bots keep the layout drawn from the game seed when the board was made
+    pub fn place_bot_fleets(&mut self) -> Result<(), String> {
+        let waiting: Vec<usize> = (0..self.number_of_players)
+            .filter(|index: &usize| {
+                self.is_bot(*index) && (self.placement_list & (1 << index)) == 0
+            })
+            .collect::<Vec<usize>>();
+        for index in waiting.into_iter() {
+            let ships: Vec<Ship> = self.boards.ship_set[index].clone();
+            self.place_fleet(index, ships)?;
+        }
+        Ok(())
+    }
+
+    pub fn bot_salvo(&self, player_index: usize) -> Vec<Target> {
+        self.bot_salvo_with(player_index, self.difficulty.strategy().as_ref())
+    }
+
+    // Aims every shot the bot has left this turn, staying on an opponent with
+    // an unfinished ship before picking a fresh one
+    pub fn bot_salvo_with(&self, player_index: usize, strategy: &dyn Strategy) -> Vec<Target> {
+        let round: u32 = match self.phase {
+            GamePhase::InProgress { round } => round,
+            _ => return Vec::new(),
+        };
+        let opponents: Vec<usize> = (0..self.number_of_players)
+            .filter(|index: &usize| *index != player_index && !self.is_eliminated(*index))
+            .collect::<Vec<usize>>();
+        let mut views: Vec<(usize, TargetView)> = opponents
+            .into_iter()
+            .map(|index: usize| (index, TargetView::from_board(&self.boards, index)))
+            .collect::<Vec<(usize, TargetView)>>();
+        let fired: usize = views
+            .iter()
+            .map(|(_, view)| view.cells.len() - view.cells_where(CellView::Unknown).len())
+            .sum();
+        let mut rng: ChaCha8Rng = self.rng(RngStream::Bot {
+            player: player_index,
+            round,
+            shot: fired,
+        });
+        let mut targets: Vec<Target> = Vec::new();
+        while targets.len() < self.shots_remaining(player_index) && !views.is_empty() {
+            // A sunk fleet only soaks up shots left over once every afloat one is gone
+            let afloat: Vec<usize> = (0..views.len())
+                .filter(|chosen: &usize| !self.boards.fleet_sunk(views[*chosen].0))
+                .collect::<Vec<usize>>();
+            let chosen: usize = views
+                .iter()
+                .position(|(_, view)| !view.cells_where(CellView::Hit).is_empty())
+                .or_else(|| afloat.choose(&mut rng).copied())
+                .unwrap_or_else(|| rng.gen_range(0..views.len()));
+            let (to, view) = &mut views[chosen];
+            match strategy.choose_target(view, &mut rng) {
+                Some((lon, lat)) => {
+                    // The outcome is unknown until the salvo lands, but the cell is spent
+                    view.set(lon, lat, CellView::Miss);
+                    targets.push(Target::new(*to, lon, lat));
+                }
+                None => {
+                    views.remove(chosen);
+                }
+            }
         }
+        targets
+    }
+
+    // Fires for every bot that still has shots and moves the game on whenever
+    // that completes a round, stopping once a human has to act
+    pub fn play_bots(&mut self) -> Vec<(usize, Target, FireOutcome)> {
+        let mut shots: Vec<(usize, Target, FireOutcome)> = Vec::new();
+        while matches!(self.phase, GamePhase::InProgress { .. }) {
+            let mut fired: bool = false;
+            for index in 0..self.number_of_players {
+                if !self.is_bot(index) || self.shots_remaining(index) == 0 {
+                    continue;
+                }
+                let targets: Vec<Target> = self.bot_salvo(index);
+                if let Ok(outcomes) = self.fire_salvo(index, &targets) {
+                    fired = true;
+                    shots.extend(
+                        targets
+                            .into_iter()
+                            .zip(outcomes)
+                            .map(|(target, outcome)| (index, target, outcome)),
+                    );
+                }
+            }
+            let advanced: bool = self.round_complete() && self.advance_round();
+            if !fired && !advanced {
+                break;
+            }
+        }
+        shots
+    }
+
+    pub fn all_fleets_placed(&self) -> bool {
+        self.placement_list == (1 << self.number_of_players) - 1
+    }
+
+    pub fn start(&mut self, challenge: String) -> Result<(), String> {
+        if !self.all_fleets_placed() {
+            return Err(format!(
+                "game: start: game {} is still waiting on fleet placements",
+                self.game_number
+            ));
+        }
+        self.transition(GamePhase::InProgress { round: 1 })?;
+        self.challenge = challenge.clone();
+        self.events.push(GameEvent::GameStarted { challenge });
+        if let TurnMode::Alternating { .. } = self.rules().turn_mode {
+            let first: usize = self
+                .rng(RngStream::TurnOrder)
+                .gen_range(0..self.number_of_players);
+            self.on_move = Some(first);
+        }
+        self.refill_shots();
+        Ok(())
+    }
+
+    pub fn is_eliminated(&self, player_index: usize) -> bool {
+        (self.eliminated & (1 << player_index)) != 0
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.phase, GamePhase::Finished { .. })
+    }
+
+    pub fn is_turn_of(&self, player_index: usize) -> bool {
+        match self.on_move {
+            Some(on_move) => on_move == player_index,
+            None => true,
+        }
+    }
+
+    pub fn turn_state(&self) -> TurnState {
+        TurnState {
+            mode: self.rules().turn_mode,
+            on_move: self.on_move,
+            shots_remaining: self.shots_remaining.clone(),
+        }
+    }
+
+    pub fn shots_per_round(&self, player_index: usize) -> usize {
+        if self.number_of_players <= player_index
+            || self.is_eliminated(player_index)
+            || !self.is_turn_of(player_index)
+        {
+            0
+        } else if self.rules().salvo {
+            self.boards
+                .ships_afloat(player_index)
+                .min(self.open_cells(player_index))
+        } else {
+            1.min(self.open_cells(player_index))
+        }
+    }
+
+    // Cells of active opponents nobody has fired at yet, which caps the shots
+    // a player can be owed so a nearly spent board cannot stall the round
+    pub fn open_cells(&self, player_index: usize) -> usize {
+        (0..self.number_of_players)
+            .filter(|index: &usize| *index != player_index && !self.is_eliminated(*index))
+            .map(|index: usize| {
+                self.boards
+                    .positions
+                    .iter()
+                    .flatten()
+                    .filter(|position: &&Position| {
+                        position.fired_state[index] == FiredState::Untouched
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    pub fn shots_remaining(&self, player_index: usize) -> usize {
+        self.shots_remaining.get(player_index).copied().unwrap_or(0)
+    }
+
+    fn refill_shots(&mut self) {
+        self.shots_remaining = (0..self.number_of_players)
+            .map(|index: usize| self.shots_per_round(index))
+            .collect::<Vec<usize>>();
+    }
+
+    pub fn fire(
+        &mut self,
+        from: usize,
+        to: usize,
+        lon: usize,
+        lat: usize,
+    ) -> Result<FireOutcome, FireError> {
+        Ok(self.fire_salvo(from, &[Target::new(to, lon, lat)])?[0])
+    }
+
+    pub fn validate_salvo(&self, from: usize, targets: &[Target]) -> Result<(), FireError> {
+        if !matches!(self.phase, GamePhase::InProgress { .. }) {
+            return Err(FireError::NotInProgress);
+        }
+        if self.number_of_players <= from {
+            return Err(FireError::UnknownPlayer(from));
+        }
+        if self.is_eliminated(from) {
+            return Err(FireError::Eliminated(from));
+        }
+        if !self.is_turn_of(from) {
+            return Err(FireError::OutOfTurn(from));
+        }
+        if targets.is_empty() {
+            return Err(FireError::EmptySalvo);
+        }
+        if self.shots_remaining(from) < targets.len() {
+            return Err(FireError::NoShotsRemaining {
+                requested: targets.len(),
+                remaining: self.shots_remaining(from),
+            });
+        }
+        for (index, target) in targets.iter().enumerate() {
+            if self.number_of_players <= target.to {
+                return Err(FireError::UnknownTarget(target.to));
+            }
+            if target.to == from {
+                return Err(FireError::SelfTarget(target.to));
+            }
+            if self.is_eliminated(target.to) {
+                return Err(FireError::TargetEliminated(target.to));
+            }
+            if !self.rules().in_bounds(target.lon, target.lat) {
+                return Err(FireError::OutOfBounds {
+                    lon: target.lon,
+                    lat: target.lat,
+                });
+            }
+            if self.boards.positions[target.lon][target.lat].fired_state[target.to]
+                != FiredState::Untouched
+                || targets[..index].contains(target)
+            {
+                return Err(FireError::AlreadyFired(*target));
+            }
+        }
+        Ok(())
+    }
+
+    // Every target is checked before any shot lands so a bad salvo fires nothing
+    pub fn fire_salvo(
+        &mut self,
+        from: usize,
+        targets: &[Target],
+    ) -> Result<Vec<FireOutcome>, FireError> {
+        self.validate_salvo(from, targets)?;
+        let chain_on_hit: bool = matches!(
+            self.rules().turn_mode,
+            TurnMode::Alternating { chain_on_hit: true }
+        );
+        let outcomes: Vec<FireOutcome> = targets
+            .iter()
+            .map(|target: &Target| self.boards.fire(target.lon, target.lat, target.to))
+            .collect::<Result<Vec<FireOutcome>, FireError>>()?;
+        for (shot, (target, outcome)) in targets.iter().zip(outcomes.iter()).enumerate() {
+            self.events.push(GameEvent::ShotFired {
+                from,
+                shot,
+                target: *target,
+                outcome: *outcome,
+            });
+            if let Some(kind) = outcome.sunk_kind() {
+                self.events.push(GameEvent::ShipSunk {
+                    player: target.to,
+                    kind,
+                });
+            }
+        }
+        let hits: usize = outcomes
+            .iter()
+            .filter(|outcome: &&FireOutcome| outcome.is_hit())
+            .count();
+        self.shots_remaining[from] -= targets.len();
+        // The bonus shots stop once there is nothing left afloat to aim at
+        if chain_on_hit
+            && (0..self.number_of_players).any(|index: usize| {
+                index != from && !self.is_eliminated(index) && !self.boards.fleet_sunk(index)
+            })
+        {
+            self.shots_remaining[from] += hits;
+        }
+        for index in 0..self.number_of_players {
+            self.shots_remaining[index] = self.shots_remaining[index].min(self.open_cells(index));
+        }
+        Ok(outcomes)
+    }
+
+    pub fn round_complete(&self) -> bool {
+        (0..self.number_of_players).all(|index: usize| self.shots_remaining(index) == 0)
+    }
+
+    // Fleets sunk during a round only eliminate their owners once every
+    // active player has fired, so the last players standing can tie
+    pub fn advance_round(&mut self) -> bool {
+        let round: u32 = match self.phase {
+            GamePhase::InProgress { round } => round,
+            _ => return false,
+        };
+        if !self.round_complete() {
+            return false;
+        }
+        let newly_eliminated: Vec<usize> = (0..self.number_of_players)
+            .filter(|index: &usize| !self.is_eliminated(*index) && self.boards.fleet_sunk(*index))
+            .collect::<Vec<usize>>();
+        for index in newly_eliminated.iter() {
+            self.eliminated |= 1 << index;
+            self.events
+                .push(GameEvent::PlayerEliminated { player: *index });
+        }
+        let survivors: Vec<usize> = (0..self.number_of_players)
+            .filter(|index: &usize| !self.is_eliminated(*index))
+            .collect::<Vec<usize>>();
+        if survivors.len() <= 1 {
+            let winners: Vec<usize> = if survivors.is_empty() {
+                newly_eliminated
+            } else {
+                survivors
+            };
+            self.on_move = None;
+            if self
+                .transition(GamePhase::Finished {
+                    winners: winners.clone(),
+                })
+                .is_err()
+            {
+                return false;
+            }
+            self.events.push(GameEvent::GameFinished { winners });
+            return true;
+        }
+        // Alternating games only start a new round once the turn wraps around
+        let next_round: bool = match self.on_move {
+            Some(on_move) => {
+                let next: usize = survivors
+                    .iter()
+                    .find(|index: &&usize| on_move < **index)
+                    .copied()
+                    .unwrap_or(survivors[0]);
+                self.on_move = Some(next);
+                next <= on_move
+            }
+            None => true,
+        };
+        if next_round
+            && self
+                .transition(GamePhase::InProgress { round: round + 1 })
+                .is_err()
+        {
+            return false;
+        }
+        self.refill_shots();
+        if let GamePhase::InProgress { round } = self.phase {
+            self.events.push(GameEvent::RoundAdvanced {
+                round,
+                on_move: self.on_move,
+            });
+        }
+        true
+    }
+
+    pub fn rules(&self) -> &GameRules {
+        &self.boards.rules
     }
 
     pub fn get_link(&self) -> String {
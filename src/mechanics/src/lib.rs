@@ -2,6 +2,11 @@ pub mod position;
 pub mod ship;
 pub mod board;
 pub mod game;
+pub mod rules;
+pub mod rng;
+pub mod event;
+pub mod notation;
+pub mod ai;
 
 // pub fn add(left: usize, right: usize) -> usize {
 //     left + right
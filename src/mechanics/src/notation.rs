@@ -0,0 +1,444 @@
+use crate::event::GameEvent;
+use crate::position::{FireOutcome, Target};
+use crate::rules::{GameRules, TurnMode};
+use crate::ship::{Ship, ShipKind};
+
+// A game record is a header of [Key "value"] tags followed by one line per event:
+//
+//   [Game "7"]
+//   [Seed "2024"]
+//   [Players "2"]
+//   [Board "10x10"]
+//   [MaxPlayers "8"]
+//   [Fleet "Carrier:5 Battleship:4 Destroyer:3 Submarine:3 Patrol_Boat:2"]
+//   [Salvo "no"]
+//   [Turns "simultaneous"]
+//
+//   p1 joins alice
+//   p1 places Carrier:A1,B1,C1,D1,E1 ...
+//   start Xq2WbL0a9c
+//   R1 p1->p2 A1 hit
+//   round 2
+//   R2 p1->p2 E1 sunk Carrier
+//   R2:2 p1->p2 A5 miss
+//   p2 eliminated
+//   winners p1
+//
+// Players are numbered from 1, cells are a column letter and a row number, and
+// R2:2 marks the second shot of a salvo. Lines starting with ; are comments.
+
+// Player ids end their join line, so they cannot break it or carry whitespace
+// at either end that reading trims away
+pub fn is_valid_player_id(player_id: &str) -> bool {
+    !player_id.is_empty()
+        && player_id.trim() == player_id
+        && !player_id.chars().any(char::is_control)
+}
+
+pub fn write(events: &[GameEvent]) -> Result<String, String> {
+    let mut lines: Vec<String> = match events.first() {
+        Some(GameEvent::GameCreated {
+            number_of_players,
+            game_number,
+            rules,
+            seed,
+        }) => header(*number_of_players, *game_number, rules, *seed),
+        _ => return Err("notation: write: log does not start with GameCreated".to_string()),
+    };
+    lines.push(String::new());
+    let mut joined: usize = 0;
+    let mut round: u32 = 0;
+    for event in events.iter().skip(1) {
+        match event {
+            GameEvent::GameCreated { .. } => {
+                return Err("notation: write: log holds more than one GameCreated".to_string())
+            }
+            GameEvent::PlayerJoined { player_id } => {
+                if !is_valid_player_id(player_id) {
+                    return Err(format!(
+                        "notation: write: player id {:?} does not fit on a join line",
+                        player_id
+                    ));
+                }
+                joined += 1;
+                lines.push(format!("p{} joins {}", joined, player_id));
+            }
+            GameEvent::FleetPlaced { player, ships } => lines.push(format!(
+                "p{} places {}",
+                player + 1,
+                ships
+                    .iter()
+                    .map(|ship: &Ship| format!(
+                        "{}:{}",
+                        kind_token(ship.kind),
+                        ship.location
+                            .iter()
+                            .map(|(x_pos, y_pos)| cell(*x_pos, *y_pos))
+                            .collect::<Vec<String>>()
+                            .join(",")
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            )),
+            GameEvent::GameStarted { challenge } => {
+                round = 1;
+                lines.push(format!("start {}", challenge));
+            }
+            GameEvent::ShotFired {
+                from,
+                shot,
+                target,
+                outcome,
+            } => {
+                let prefix: String = match shot {
+                    0 => format!("R{}", round),
+                    _ => format!("R{}:{}", round, shot + 1),
+                };
+                lines.push(format!(
+                    "{} p{}->p{} {} {}",
+                    prefix,
+                    from + 1,
+                    target.to + 1,
+                    cell(target.lon, target.lat),
+                    outcome_token(outcome)
+                ));
+            }
+            // Every sinking shot already names the ship, so the reader restores these
+            GameEvent::ShipSunk { .. } => (),
+            GameEvent::PlayerEliminated { player } => {
+                lines.push(format!("p{} eliminated", player + 1))
+            }
+            GameEvent::RoundAdvanced {
+                round: next_round,
+                on_move,
+            } => {
+                round = *next_round;
+                lines.push(match on_move {
+                    Some(on_move) => format!("round {} p{}", next_round, on_move + 1),
+                    None => format!("round {}", next_round),
+                });
+            }
+            GameEvent::GameFinished { winners } => lines.push(format!(
+                "winners {}",
+                winners
+                    .iter()
+                    .map(|index: &usize| format!("p{}", index + 1))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            )),
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+pub fn read(record: &str) -> Result<Vec<GameEvent>, String> {
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut events: Vec<GameEvent> = Vec::new();
+    let mut joined: usize = 0;
+    let mut round: u32 = 0;
+    for (index, line) in record.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let error = |message: String| -> String {
+            format!("notation: read: line {}: {}", index + 1, message)
+        };
+        if line.starts_with('[') {
+            if !events.is_empty() {
+                return Err(error("header tag after the first move".to_string()));
+            }
+            tags.push(tag(line).map_err(error)?);
+            continue;
+        }
+        if events.is_empty() {
+            events.push(created(&tags).map_err(error)?);
+        }
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let event: GameEvent = match word {
+            "start" => {
+                round = 1;
+                GameEvent::GameStarted {
+                    challenge: rest.to_string(),
+                }
+            }
+            "round" => {
+                let mut words = rest.split_whitespace();
+                round = number(words.next().unwrap_or("")).map_err(error)?;
+                GameEvent::RoundAdvanced {
+                    round,
+                    on_move: words.next().map(player).transpose().map_err(error)?,
+                }
+            }
+            "winners" => GameEvent::GameFinished {
+                winners: rest
+                    .split_whitespace()
+                    .map(player)
+                    .collect::<Result<Vec<usize>, String>>()
+                    .map_err(error)?,
+            },
+            _ if word.starts_with('R') => {
+                let event: GameEvent = shot(word, rest, round, &events).map_err(error)?;
+                events.push(event.clone());
+                if let GameEvent::ShotFired {
+                    target, outcome, ..
+                } = event
+                {
+                    if let Some(kind) = outcome.sunk_kind() {
+                        events.push(GameEvent::ShipSunk {
+                            player: target.to,
+                            kind,
+                        });
+                    }
+                }
+                continue;
+            }
+            _ => {
+                let index: usize = player(word).map_err(error)?;
+                let (verb, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                match verb {
+                    "joins" => {
+                        joined += 1;
+                        if index + 1 != joined {
+                            return Err(error(format!("expected p{} to join next", joined)));
+                        }
+                        GameEvent::PlayerJoined {
+                            player_id: rest.to_string(),
+                        }
+                    }
+                    "places" => GameEvent::FleetPlaced {
+                        player: index,
+                        ships: rest
+                            .split_whitespace()
+                            .map(ship)
+                            .collect::<Result<Vec<Ship>, String>>()
+                            .map_err(error)?,
+                    },
+                    "eliminated" => GameEvent::PlayerEliminated { player: index },
+                    _ => return Err(error(format!("unknown move {}", line))),
+                }
+            }
+        };
+        events.push(event);
+    }
+    if events.is_empty() {
+        events.push(
+            created(&tags).map_err(|message: String| format!("notation: read: {}", message))?,
+        );
+    }
+    Ok(events)
+}
+
+fn header(number_of_players: usize, game_number: u64, rules: &GameRules, seed: u64) -> Vec<String> {
+    let turns: &str = match rules.turn_mode {
+        TurnMode::Simultaneous => "simultaneous",
+        TurnMode::Alternating {
+            chain_on_hit: false,
+        } => "alternating",
+        TurnMode::Alternating { chain_on_hit: true } => "alternating chain",
+    };
+    let mut tags: Vec<String> = vec![
+        format!("[Game \"{}\"]", game_number),
+        format!("[Seed \"{}\"]", seed),
+        format!("[Players \"{}\"]", number_of_players),
+        format!("[Board \"{}x{}\"]", rules.width, rules.height),
+        format!("[MaxPlayers \"{}\"]", rules.max_players),
+        format!(
+            "[Fleet \"{}\"]",
+            rules
+                .fleet
+                .iter()
+                .map(|(kind, size)| format!("{}:{}", kind_token(*kind), size))
+                .collect::<Vec<String>>()
+                .join(" ")
+        ),
+        format!("[Salvo \"{}\"]", if rules.salvo { "yes" } else { "no" }),
+        format!("[Turns \"{}\"]", turns),
+    ];
+    // Only written when set, so records from before the tag still read the same
+    if rules.no_touching {
+        tags.push("[NoTouching \"yes\"]".to_string());
+    }
+    tags
+}
+
+fn created(tags: &[(String, String)]) -> Result<GameEvent, String> {
+    let value = |key: &str| -> Result<&str, String> {
+        tags.iter()
+            .find(|(tag_key, _)| tag_key.eq(key))
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| format!("missing [{} \"...\"] header", key))
+    };
+    let (width, height) = value("Board")?
+        .split_once('x')
+        .ok_or_else(|| "board must be written as WIDTHxHEIGHT".to_string())?;
+    let fleet: Vec<(ShipKind, usize)> = value("Fleet")?
+        .split_whitespace()
+        .map(|entry: &str| -> Result<(ShipKind, usize), String> {
+            let (kind, size) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("fleet entry {} must be Kind:length", entry))?;
+            Ok((ship_kind(kind)?, number(size)?))
+        })
+        .collect::<Result<Vec<(ShipKind, usize)>, String>>()?;
+    let mut rules: GameRules = GameRules::new(
+        number(width)?,
+        number(height)?,
+        fleet,
+        number(value("MaxPlayers")?)?,
+    );
+    rules.salvo = match value("Salvo")? {
+        "yes" => true,
+        "no" => false,
+        other => return Err(format!("salvo must be yes or no, not {}", other)),
+    };
+    rules.turn_mode = match value("Turns")? {
+        "simultaneous" => TurnMode::Simultaneous,
+        "alternating" => TurnMode::Alternating {
+            chain_on_hit: false,
+        },
+        "alternating chain" => TurnMode::Alternating { chain_on_hit: true },
+        other => return Err(format!("unknown turn mode {}", other)),
+    };
+    rules.no_touching = match value("NoTouching") {
+        Ok("yes") => true,
+        Ok("no") | Err(_) => false,
+        Ok(other) => return Err(format!("no touching must be yes or no, not {}", other)),
+    };
+    // A record is untrusted, so its board and seats are checked before replay allocates them
+    let number_of_players: usize = number(value("Players")?)?;
+    rules.validate(number_of_players)?;
+    Ok(GameEvent::GameCreated {
+        number_of_players,
+        game_number: number(value("Game")?)?,
+        rules,
+        seed: number(value("Seed")?)?,
+    })
+}
+
+fn tag(line: &str) -> Result<(String, String), String> {
+    line.strip_prefix('[')
+        .and_then(|line: &str| line.strip_suffix("\"]"))
+        .and_then(|line: &str| line.split_once(" \""))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("malformed header tag {}", line))
+}
+
+fn shot(word: &str, rest: &str, round: u32, events: &[GameEvent]) -> Result<GameEvent, String> {
+    let (shot_round, shot) = match word[1..].split_once(':') {
+        Some((shot_round, shot)) => (number::<u32>(shot_round)?, number::<usize>(shot)?),
+        None => (number::<u32>(&word[1..])?, 1),
+    };
+    if shot_round != round || shot == 0 {
+        return Err(format!("{} does not belong to round {}", word, round));
+    }
+    let mut words = rest.split_whitespace();
+    let (from, to) = words
+        .next()
+        .and_then(|players: &str| players.split_once("->"))
+        .ok_or_else(|| "shots must name players as pN->pM".to_string())?;
+    let (lon, lat) = parse_cell(words.next().unwrap_or(""))?;
+    let target: Target = Target::new(player(to)?, lon, lat);
+    let outcome: FireOutcome = match (words.next(), words.next()) {
+        (Some("miss"), None) => FireOutcome::Miss,
+        (Some("repeat"), None) => FireOutcome::AlreadyFired,
+        // Hits only name their ship when it sinks, so look it up in the placed fleet
+        (Some("hit"), None) => FireOutcome::Hit {
+            kind: placed_kind(events, target)?,
+        },
+        (Some("sunk"), Some(kind)) => FireOutcome::Sunk {
+            kind: ship_kind(kind)?,
+        },
+        (Some("eliminated"), Some(kind)) => FireOutcome::PlayerEliminated {
+            kind: ship_kind(kind)?,
+        },
+        _ => return Err(format!("unknown shot outcome in {} {}", word, rest)),
+    };
+    Ok(GameEvent::ShotFired {
+        from: player(from)?,
+        shot: shot - 1,
+        target,
+        outcome,
+    })
+}
+
+fn placed_kind(events: &[GameEvent], target: Target) -> Result<ShipKind, String> {
+    events
+        .iter()
+        .find_map(|event: &GameEvent| match event {
+            GameEvent::FleetPlaced { player, ships } if *player == target.to => ships
+                .iter()
+                .find(|ship: &&Ship| ship.location.contains(&(target.lon, target.lat)))
+                .map(|ship: &Ship| ship.kind),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            format!(
+                "hit on {} has no ship of p{} under it",
+                cell(target.lon, target.lat),
+                target.to + 1
+            )
+        })
+}
+
+fn ship(entry: &str) -> Result<Ship, String> {
+    let (kind, cells) = entry
+        .split_once(':')
+        .ok_or_else(|| format!("ship {} must be Kind:cells", entry))?;
+    Ok(Ship {
+        kind: ship_kind(kind)?,
+        location: cells
+            .split(',')
+            .filter(|cell: &&str| !cell.is_empty())
+            .map(parse_cell)
+            .collect::<Result<Vec<(usize, usize)>, String>>()?,
+    })
+}
+
+fn outcome_token(outcome: &FireOutcome) -> String {
+    match outcome {
+        FireOutcome::Miss => "miss".to_string(),
+        FireOutcome::Hit { .. } => "hit".to_string(),
+        FireOutcome::Sunk { kind } => format!("sunk {}", kind_token(*kind)),
+        FireOutcome::AlreadyFired => "repeat".to_string(),
+        FireOutcome::PlayerEliminated { kind } => format!("eliminated {}", kind_token(*kind)),
+    }
+}
+
+fn kind_token(kind: ShipKind) -> String {
+    kind.display_name().replace(' ', "_")
+}
+
+fn ship_kind(token: &str) -> Result<ShipKind, String> {
+    ShipKind::from_name(&token.replace('_', " "))
+        .ok_or_else(|| format!("unknown ship kind {}", token))
+}
+
+fn cell(lon: usize, lat: usize) -> String {
+    format!("{}{}", (b'A' + lon as u8) as char, lat + 1)
+}
+
+fn parse_cell(token: &str) -> Result<(usize, usize), String> {
+    let mut chars = token.chars();
+    let lon: usize = match chars.next() {
+        Some(column) if column.is_ascii_uppercase() => column as usize - 'A' as usize,
+        _ => return Err(format!("cell {} must start with a column letter", token)),
+    };
+    match number::<usize>(chars.as_str())? {
+        0 => Err(format!("cell {} has no row 0", token)),
+        row => Ok((lon, row - 1)),
+    }
+}
+
+fn player(token: &str) -> Result<usize, String> {
+    match token.strip_prefix('p').map(number::<usize>) {
+        Some(Ok(index)) if index > 0 => Ok(index - 1),
+        _ => Err(format!("{} is not a player like p1", token)),
+    }
+}
+
+fn number<T: std::str::FromStr>(token: &str) -> Result<T, String> {
+    token
+        .parse::<T>()
+        .map_err(|_| format!("{} is not a number", token))
+}
@@ -1,39 +1,133 @@
+use crate::rules::GameRules;
+use crate::ship::ShipKind;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Target {
+    pub to: usize,
+    pub lon: usize,
+    pub lat: usize,
+}
+
+impl Target {
+    pub fn new(to: usize, lon: usize, lat: usize) -> Self {
+        Self { to, lon, lat }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FireOutcome {
+    Miss,
+    Hit { kind: ShipKind },
+    Sunk { kind: ShipKind },
+    AlreadyFired,
+    // The shot sank the last ship afloat in the target's fleet
+    PlayerEliminated { kind: ShipKind },
+}
+
+impl FireOutcome {
+    pub fn is_hit(&self) -> bool {
+        matches!(
+            self,
+            Self::Hit { .. } | Self::Sunk { .. } | Self::PlayerEliminated { .. }
+        )
+    }
+
+    pub fn sunk_kind(&self) -> Option<ShipKind> {
+        match self {
+            Self::Sunk { kind } | Self::PlayerEliminated { kind } => Some(*kind),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FireOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Miss => write!(f, "miss"),
+            Self::Hit { kind } => write!(f, "hit a {}", kind),
+            Self::Sunk { kind } => write!(f, "sunk a {}", kind),
+            Self::AlreadyFired => write!(f, "already fired on"),
+            Self::PlayerEliminated { kind } => write!(f, "sunk a {} and the whole fleet", kind),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum FireError {
+    NotInProgress,
+    UnknownPlayer(usize),
+    Eliminated(usize),
+    OutOfTurn(usize),
+    EmptySalvo,
+    NoShotsRemaining { requested: usize, remaining: usize },
+    UnknownTarget(usize),
+    SelfTarget(usize),
+    TargetEliminated(usize),
+    OutOfBounds { lon: usize, lat: usize },
+    AlreadyFired(Target),
+}
+
+impl fmt::Display for FireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInProgress => write!(f, "the game is not in progress"),
+            Self::UnknownPlayer(from) => write!(f, "player {} is not in this game", from),
+            Self::Eliminated(from) => write!(f, "player {} has been eliminated", from),
+            Self::OutOfTurn(from) => write!(f, "it is not player {}'s turn", from),
+            Self::EmptySalvo => write!(f, "no targets were given"),
+            Self::NoShotsRemaining {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "{} shots were requested with {} remaining this round",
+                requested, remaining
+            ),
+            Self::UnknownTarget(to) => write!(f, "player {} is not in this game", to),
+            Self::SelfTarget(to) => write!(f, "player {} cannot fire at their own board", to),
+            Self::TargetEliminated(to) => write!(f, "player {} has already been eliminated", to),
+            Self::OutOfBounds { lon, lat } => write!(f, "({}, {}) is off the board", lon, lat),
+            Self::AlreadyFired(target) => write!(
+                f,
+                "({}, {}) on player {}'s board has already been fired on",
+                target.lon, target.lat, target.to
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FireError {}
 
 #[derive(Serialize, Deserialize)]
 pub struct FirePosition {
     pub challenge: Vec<u8>,
     pub from: usize,
-    pub to: usize,
-    pub lon: usize,
-    pub lat: usize,
+    pub targets: Vec<Target>,
 }
 
 impl FirePosition {
-    pub fn new(challenge: Vec<u8>, from: usize, to: usize, lon: usize, lat: usize) -> Self {
+    pub fn new(challenge: Vec<u8>, from: usize, targets: Vec<Target>) -> Self {
         Self {
             challenge,
             from,
-            to,
-            lon,
-            lat,
+            targets,
         }
     }
 }
 
-const SQUARE_SIDE: usize = 10;
-const PLAYERS: usize = 2;
-
-#[derive(Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub enum FiredState {
     Hit,
     Miss,
     Untouched,
     Empty,
-    Ship(String),
+    Ship(ShipKind),
+    Sunk(ShipKind),
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Position {
     pub fired_state: Vec<FiredState>,
 }
@@ -44,46 +138,43 @@ impl Position {
         lat: usize,
         shots: Option<Vec<FiredState>>,
         size: usize,
+        rules: &GameRules,
     ) -> Result<Self, String> {
-        if SQUARE_SIDE - 1 < lat {
-            return Err(format!(
-                "Position: {}: lat {} is greater than {}",
-                "lat",
-                lat,
-                SQUARE_SIDE - 1
-            ));
-        } else if SQUARE_SIDE - 1 < lon {
-            return Err(format!(
-                "Position: {}: lon {} is greater than {}",
-                "lon",
-                lon,
-                SQUARE_SIDE - 1
-            ));
-        }
+        Self::check_bounds(lon, lat, rules)?;
         Ok(Self {
             fired_state: shots.unwrap_or(vec![FiredState::Empty; size]),
         })
     }
 
-    pub fn update(lat: usize, lon: usize) -> Result<Self, String> {
-        if SQUARE_SIDE - 1 < lat {
+    pub fn update(
+        lat: usize,
+        lon: usize,
+        players: usize,
+        rules: &GameRules,
+    ) -> Result<Self, String> {
+        Self::check_bounds(lon, lat, rules)?;
+        Ok(Self {
+            fired_state: vec![FiredState::Untouched; players],
+        })
+    }
+
+    fn check_bounds(lon: usize, lat: usize, rules: &GameRules) -> Result<(), String> {
+        if rules.height - 1 < lat {
             return Err(format!(
                 "Position: {}: lat {} is greater than {}",
                 "lat",
                 lat,
-                SQUARE_SIDE - 1
+                rules.height - 1
             ));
-        } else if SQUARE_SIDE - 1 < lon {
+        } else if rules.width - 1 < lon {
             return Err(format!(
                 "Position: {}: lon {} is greater than {}",
                 "lon",
                 lon,
-                SQUARE_SIDE - 1
+                rules.width - 1
             ));
         }
-        Ok(Self {
-            fired_state: vec![FiredState::Untouched; PLAYERS],
-        })
+        Ok(())
     }
 
     pub fn get_fired_state(&self) -> Vec<FiredState> {
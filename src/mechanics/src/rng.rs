@@ -0,0 +1,57 @@
+use rand::distributions::Alphanumeric;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+const CHALLENGE_LENGTH: usize = 10;
+
+// Every use of randomness reads its own stream of the game seed, so replaying a
+// seed reproduces each of them no matter what order the requests arrived in
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RngStream {
+    Placement,
+    Challenge,
+    TurnOrder,
+    Bot {
+        player: usize,
+        round: u32,
+        shot: usize,
+    },
+}
+
+impl RngStream {
+    fn id(&self) -> u64 {
+        match self {
+            Self::Placement => 1,
+            Self::Challenge => 2,
+            Self::TurnOrder => 3,
+            Self::Bot {
+                player,
+                round,
+                shot,
+            } => {
+                (4 << 60)
+                    | ((*player as u64 & 0xff) << 52)
+                    | ((*round as u64) << 20)
+                    | (*shot as u64 & 0xf_ffff)
+            }
+        }
+    }
+}
+
+pub fn game_rng(seed: u64, stream: RngStream) -> ChaCha8Rng {
+    let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream.id());
+    rng
+}
+
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+pub fn challenge<R: Rng + ?Sized>(rng: &mut R) -> String {
+    rng.sample_iter(&Alphanumeric)
+        .take(CHALLENGE_LENGTH)
+        .map(char::from)
+        .collect::<String>()
+}
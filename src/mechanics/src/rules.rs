@@ -0,0 +1,149 @@
+use crate::ship::{FleetPreset, Ship, ShipKind};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+const MAX_SIDE: usize = 26;
+const MAX_PLAYERS: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TurnMode {
+    #[default]
+    Simultaneous,
+    // One player fires at a time, optionally firing again after every new hit
+    Alternating {
+        chain_on_hit: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct GameRules {
+    pub width: usize,
+    pub height: usize,
+    pub fleet: Vec<(ShipKind, usize)>,
+    pub max_players: usize,
+    // Salvo games give each player one shot per unsunk ship every round
+    #[serde(default)]
+    pub salvo: bool,
+    #[serde(default)]
+    pub turn_mode: TurnMode,
+    // Randomly placed fleets keep a cell of water around every ship
+    #[serde(default)]
+    pub no_touching: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 10,
+            fleet: Ship::fleet(),
+            max_players: MAX_PLAYERS,
+            salvo: false,
+            turn_mode: TurnMode::Simultaneous,
+            no_touching: false,
+        }
+    }
+}
+
+impl GameRules {
+    pub fn new(
+        width: usize,
+        height: usize,
+        fleet: Vec<(ShipKind, usize)>,
+        max_players: usize,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            fleet,
+            max_players,
+            salvo: false,
+            turn_mode: TurnMode::Simultaneous,
+            no_touching: false,
+        }
+    }
+
+    pub fn with_preset(
+        width: usize,
+        height: usize,
+        preset: FleetPreset,
+        max_players: usize,
+    ) -> Result<Self, String> {
+        let rules: Self = Self::new(width, height, preset.fleet(), max_players);
+        rules.validate_fleet()?;
+        Ok(rules)
+    }
+
+    pub fn validate(&self, number_of_players: usize) -> Result<(), String> {
+        if self.max_players < 2 || u32::BITS as usize <= self.max_players {
+            return Err(format!(
+                "rules: validate: max_players {} must be between 2 and {}",
+                self.max_players,
+                u32::BITS - 1
+            ));
+        }
+        if number_of_players < 2 || self.max_players < number_of_players {
+            return Err(format!(
+                "rules: validate: {} players is outside of the allowed 2 to {} range",
+                number_of_players, self.max_players
+            ));
+        }
+        self.validate_fleet()
+    }
+
+    // Checks that the board and fleet are usable regardless of player count
+    pub fn validate_fleet(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 || MAX_SIDE < self.width || MAX_SIDE < self.height {
+            return Err(format!(
+                "rules: validate_fleet: a {}x{} board is outside of the supported 1x1 to {}x{} range",
+                self.width, self.height, MAX_SIDE, MAX_SIDE
+            ));
+        }
+        if self.fleet.is_empty() {
+            return Err(
+                "rules: validate_fleet: the fleet must contain at least one ship".to_string(),
+            );
+        }
+        if let Some((name, length)) = self
+            .fleet
+            .iter()
+            .find(|(_, length)| *length == 0 || (self.width < *length && self.height < *length))
+        {
+            return Err(format!(
+                "rules: validate_fleet: {} of length {} does not fit on a {}x{} board",
+                name, length, self.width, self.height
+            ));
+        }
+        // The AI tells sunk ships apart by kind, which only works if a kind has one length
+        if let Some((name, length)) = self.fleet.iter().find(|(name, length)| {
+            self.fleet
+                .iter()
+                .any(|(other, other_length)| other == name && other_length != length)
+        }) {
+            return Err(format!(
+                "rules: validate_fleet: {} of length {} differs from another {} in the fleet",
+                name, length, name
+            ));
+        }
+        if self.width * self.height < self.fleet.iter().map(|(_, length)| length).sum() {
+            return Err(format!(
+                "rules: validate_fleet: the fleet occupies more cells than a {}x{} board has",
+                self.width, self.height
+            ));
+        }
+        // A fixed seed keeps validation deterministic while proving a layout exists
+        Ship::random_ships(&mut StdRng::seed_from_u64(0), self, self.no_touching)
+            .map(|_| ())
+            .map_err(|_| {
+                format!(
+                    "rules: validate_fleet: the fleet cannot be arranged on a {}x{} board",
+                    self.width, self.height
+                )
+            })
+    }
+
+    pub fn in_bounds(&self, lon: usize, lat: usize) -> bool {
+        lon < self.width && lat < self.height
+    }
+}
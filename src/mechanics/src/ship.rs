@@ -1,12 +1,206 @@
-use serde::{Deserialize, Serialize};
+use crate::rules::GameRules;
+use rand::Rng;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Clone)]
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ShipKind {
+    Carrier,
+    Battleship,
+    Submarine,
+    Destroyer,
+    PatrolBoat,
+    Cruiser,
+}
+
+impl ShipKind {
+    pub const ALL: [ShipKind; 6] = [
+        Self::Carrier,
+        Self::Battleship,
+        Self::Submarine,
+        Self::Destroyer,
+        Self::PatrolBoat,
+        Self::Cruiser,
+    ];
+
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Carrier => 0,
+            Self::Battleship => 1,
+            Self::Submarine => 2,
+            Self::Destroyer => 3,
+            Self::PatrolBoat => 4,
+            Self::Cruiser => 5,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind: &Self| kind.id() == id)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind: &Self| kind.asset_key().eq(name) || kind.display_name().eq(name))
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Carrier => "Carrier",
+            Self::Battleship => "Battleship",
+            Self::Submarine => "Submarine",
+            Self::Destroyer => "Destroyer",
+            Self::PatrolBoat => "Patrol Boat",
+            Self::Cruiser => "Cruiser",
+        }
+    }
+
+    // Used for both the css class and the ships_{asset_key}_day.svg file name
+    pub fn asset_key(&self) -> &'static str {
+        match self {
+            Self::Carrier => "Carrier",
+            Self::Battleship => "Battleship",
+            Self::Submarine => "Submarine",
+            Self::Destroyer => "Destroyer",
+            Self::PatrolBoat => "Patrol_Boat",
+            Self::Cruiser => "Cruiser",
+        }
+    }
+}
+
+impl fmt::Display for ShipKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+impl Serialize for ShipKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.id())
+    }
+}
+
+// Games stored before ShipKind existed hold the ship name as a string
+struct ShipKindVisitor;
+
+impl<'de> Visitor<'de> for ShipKindVisitor {
+    type Value = ShipKind;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a ship kind id or ship name")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<ShipKind, E> {
+        u8::try_from(value)
+            .ok()
+            .and_then(ShipKind::from_id)
+            .ok_or_else(|| E::custom(format!("unknown ship kind id {}", value)))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<ShipKind, E> {
+        u64::try_from(value)
+            .map_err(|_| E::custom(format!("unknown ship kind id {}", value)))
+            .and_then(|value: u64| self.visit_u64(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<ShipKind, E> {
+        ShipKind::from_name(value).ok_or_else(|| E::custom(format!("unknown ship name {}", value)))
+    }
+}
+
+impl<'de> Deserialize<'de> for ShipKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ShipKindVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FleetPreset {
+    #[default]
+    Classic1990,
+    Classic1967,
+    Russian,
+}
+
+impl FleetPreset {
+    pub const ALL: [FleetPreset; 3] = [Self::Classic1990, Self::Classic1967, Self::Russian];
+
+    pub fn fleet(&self) -> Vec<(ShipKind, usize)> {
+        match self {
+            Self::Classic1990 => vec![
+                (ShipKind::Carrier, 5),
+                (ShipKind::Battleship, 4),
+                (ShipKind::Destroyer, 3),
+                (ShipKind::Submarine, 3),
+                (ShipKind::PatrolBoat, 2),
+            ],
+            Self::Classic1967 => vec![
+                (ShipKind::Carrier, 5),
+                (ShipKind::Battleship, 4),
+                (ShipKind::Cruiser, 3),
+                (ShipKind::Submarine, 3),
+                (ShipKind::Destroyer, 2),
+            ],
+            Self::Russian => vec![
+                (ShipKind::Battleship, 4),
+                (ShipKind::Cruiser, 3),
+                (ShipKind::Cruiser, 3),
+                (ShipKind::Destroyer, 2),
+                (ShipKind::Destroyer, 2),
+                (ShipKind::Destroyer, 2),
+                (ShipKind::Submarine, 1),
+                (ShipKind::Submarine, 1),
+                (ShipKind::Submarine, 1),
+                (ShipKind::Submarine, 1),
+            ],
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Classic1990 => "Classic (1990)",
+            Self::Classic1967 => "Classic (1967)",
+            Self::Russian => "Russian",
+        }
+    }
+}
+
+impl fmt::Display for FleetPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Ship {
-    pub name: String,
+    #[serde(alias = "name")]
+    pub kind: ShipKind,
     pub location: Vec<(usize, usize)>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct FleetPlacement {
+    pub access_key: String,
+    pub from: usize,
+    pub ships: Vec<Ship>,
+}
+
+impl FleetPlacement {
+    pub fn new(access_key: String, from: usize, ships: Vec<Ship>) -> Self {
+        Self {
+            access_key,
+            from,
+            ships,
+        }
+    }
+}
+
 #[allow(dead_code)]
+#[derive(Clone, Copy)]
 enum Direction {
     North,
     West,
@@ -15,30 +209,25 @@ enum Direction {
 }
 
 impl Ship {
-    pub fn new(ship_name: String, pos: Result<Vec<(usize, usize)>, String>) -> Self {
+    pub fn new(kind: ShipKind, pos: Result<Vec<(usize, usize)>, String>) -> Self {
         match pos {
-            Ok(p) => Self {
-                name: ship_name,
-                location: p,
-            },
-            Err(e) => panic!("{}: {}", ship_name, e),
+            Ok(p) => Self { kind, location: p },
+            Err(e) => panic!("{}: {}", kind, e),
         }
     }
 
+    pub fn fleet() -> Vec<(ShipKind, usize)> {
+        FleetPreset::default().fleet()
+    }
+
     pub fn new_ships() -> Vec<Self> {
-        [
-            ("Carrier".to_string(), 2),
-            ("Battleship".to_string(), 3),
-            ("Submarine".to_string(), 3),
-            ("Destroyer".to_string(), 4),
-            ("Patrol_Boat".to_string(), 5),
-        ]
-        .into_iter()
-        .enumerate()
-        .map(|(index, (name, size))| {
-            Self::new(name, Self::direction((0, index), size, Direction::East))
-        })
-        .collect::<Vec<Self>>() // make sure to include ship collision detection
+        Self::fleet()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (kind, size))| {
+                Self::new(kind, Self::direction((0, index), size, Direction::East))
+            })
+            .collect::<Vec<Self>>()
     }
 
     fn direction(
@@ -64,10 +253,165 @@ impl Ship {
             .collect::<Vec<(usize, usize)>>())
     }
 
+    pub fn random_ships<R: Rng + ?Sized>(
+        rng: &mut R,
+        rules: &GameRules,
+        no_touching: bool,
+    ) -> Result<Vec<Self>, String> {
+        let fleet: &[(ShipKind, usize)] = &rules.fleet;
+        let mut fleet_order: Vec<&(ShipKind, usize)> = fleet.iter().collect();
+        fleet_order.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let mut blocked: HashSet<(usize, usize)> = HashSet::new();
+            let mut ships: Vec<Self> = Vec::with_capacity(fleet.len());
+            for (kind, size) in fleet_order.iter() {
+                match Self::random_location(rng, rules, *size, &blocked) {
+                    Some(location) => {
+                        for (x_pos, y_pos) in location.iter() {
+                            if no_touching {
+                                for x_near in x_pos.saturating_sub(1)..=x_pos + 1 {
+                                    for y_near in y_pos.saturating_sub(1)..=y_pos + 1 {
+                                        blocked.insert((x_near, y_near));
+                                    }
+                                }
+                            } else {
+                                blocked.insert((*x_pos, *y_pos));
+                            }
+                        }
+                        ships.push(Self::new(*kind, Ok(location)));
+                    }
+                    None => break,
+                }
+            }
+            if ships.len() == fleet.len() {
+                ships.sort_by_key(|ship: &Self| {
+                    fleet
+                        .iter()
+                        .position(|(kind, _)| ship.kind == *kind)
+                        .unwrap_or(0)
+                });
+                return Ok(ships);
+            }
+        }
+        Err(format!(
+            "ship: random_ships: could not fit the fleet {:?} on a {}x{} board",
+            fleet, rules.width, rules.height
+        ))
+    }
+
+    fn random_location<R: Rng + ?Sized>(
+        rng: &mut R,
+        rules: &GameRules,
+        size: usize,
+        blocked: &HashSet<(usize, usize)>,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut directions: Vec<Direction> = Vec::with_capacity(2);
+        if 0 < size && size <= rules.width {
+            directions.push(Direction::East);
+        }
+        if 0 < size && size <= rules.height {
+            directions.push(Direction::North);
+        }
+        if directions.is_empty() {
+            return None;
+        }
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let direction: Direction = directions[rng.gen_range(0..directions.len())];
+            let (x_limit, y_limit): (usize, usize) = match direction {
+                Direction::East => (rules.width - size + 1, rules.height),
+                _ => (rules.width, rules.height - size + 1),
+            };
+            let location: Vec<(usize, usize)> = Self::direction(
+                (rng.gen_range(0..x_limit), rng.gen_range(0..y_limit)),
+                size,
+                direction,
+            )
+            .ok()?;
+            if location.iter().all(|position| !blocked.contains(position)) {
+                return Some(location);
+            }
+        }
+        None
+    }
+
     pub fn check_hit(&self, index: usize, jndex: usize) -> bool {
         self.location
             .iter()
-            .fold(false, |acc, pos| acc || (pos.0 == index && pos.1 == jndex))
+            .any(|pos| pos.0 == index && pos.1 == jndex)
+    }
+
+    pub fn is_straight(&self) -> bool {
+        if self.location.is_empty() {
+            return false;
+        }
+        let mut cells: Vec<(usize, usize)> = self.location.clone();
+        cells.sort_unstable();
+        let horizontal: bool = cells.iter().all(|(_, y_pos)| *y_pos == cells[0].1);
+        let vertical: bool = cells.iter().all(|(x_pos, _)| *x_pos == cells[0].0);
+        cells.windows(2).all(|pair| {
+            (horizontal && pair[1].0 == pair[0].0 + 1) || (vertical && pair[1].1 == pair[0].1 + 1)
+        }) && (horizontal || vertical)
+    }
+
+    pub fn validate_fleet(ships: &[Ship], rules: &GameRules) -> Result<(), String> {
+        let mut expected: Vec<(ShipKind, usize)> = rules.fleet.clone();
+        let mut submitted: Vec<(ShipKind, usize)> = ships
+            .iter()
+            .map(|ship: &Ship| (ship.kind, ship.location.len()))
+            .collect::<Vec<(ShipKind, usize)>>();
+        expected.sort();
+        submitted.sort();
+        if expected != submitted {
+            return Err(format!(
+                "ship: validate_fleet: submitted fleet {:?} does not match the expected fleet {:?}",
+                submitted, expected
+            ));
+        }
+        let mut occupied: HashSet<(usize, usize)> = HashSet::new();
+        for ship in ships.iter() {
+            if let Some((x_pos, y_pos)) = ship
+                .location
+                .iter()
+                .find(|(x_pos, y_pos)| !rules.in_bounds(*x_pos, *y_pos))
+            {
+                return Err(format!(
+                    "ship: validate_fleet: {} at ({}, {}) is outside of the {}x{} board",
+                    ship.kind, x_pos, y_pos, rules.width, rules.height
+                ));
+            }
+            if !ship.is_straight() {
+                return Err(format!(
+                    "ship: validate_fleet: {} is not placed in a contiguous straight line",
+                    ship.kind
+                ));
+            }
+            for position in ship.location.iter() {
+                if !occupied.insert(*position) {
+                    return Err(format!(
+                        "ship: validate_fleet: {} overlaps another ship at ({}, {})",
+                        ship.kind, position.0, position.1
+                    ));
+                }
+            }
+        }
+        // Spaced out fleets may not share an edge or even a corner
+        if rules.no_touching {
+            for (index, ship) in ships.iter().enumerate() {
+                for (x_pos, y_pos) in ship.location.iter() {
+                    if let Some(other) = ships[index + 1..].iter().find(|other: &&Ship| {
+                        other.location.iter().any(|(x_near, y_near)| {
+                            x_near.abs_diff(*x_pos) <= 1 && y_near.abs_diff(*y_pos) <= 1
+                        })
+                    }) {
+                        return Err(format!(
+                            "ship: validate_fleet: {} touches {} at ({}, {})",
+                            ship.kind, other.kind, x_pos, y_pos
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -75,9 +419,18 @@ impl Ship {
 pub struct ShipSet;
 
 impl ShipSet {
-    pub fn new(players: usize) -> Vec<Vec<Ship>> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(players: usize, rules: &GameRules) -> Result<Vec<Vec<Ship>>, String> {
+        Self::new_with_rng(players, rules, &mut rand::thread_rng())
+    }
+
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        players: usize,
+        rules: &GameRules,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<Ship>>, String> {
         (0..players)
-            .map(|_| Ship::new_ships())
-            .collect::<Vec<Vec<Ship>>>()
+            .map(|_| Ship::random_ships(rng, rules, rules.no_touching))
+            .collect::<Result<Vec<Vec<Ship>>, String>>()
     }
 }
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    mod ai {
+        use mechanics::ai::{
+            CellView, Density, Difficulty, HuntTarget, RandomFire, Strategy, TargetView,
+        };
+        use mechanics::board::Board;
+        use mechanics::rules::GameRules;
+        use mechanics::ship::{FleetPreset, ShipKind, ShipSet};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        fn empty_view() -> TargetView {
+            TargetView::new(10, 10, GameRules::default().fleet)
+        }
+
+        #[test]
+        fn test_hunt_searches_the_checkerboard() {
+            let mut rng: StdRng = StdRng::seed_from_u64(1);
+            let mut view: TargetView = empty_view();
+            for _ in 0..50 {
+                let (x_pos, y_pos) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+                assert_eq!((x_pos + y_pos) % 2, 0);
+                assert_eq!(view.get(x_pos, y_pos), Some(CellView::Unknown));
+                view.set(x_pos, y_pos, CellView::Miss);
+            }
+            // Once the checkerboard is used up every remaining cell is fair game
+            let (x_pos, y_pos) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+            assert_eq!((x_pos + y_pos) % 2, 1);
+        }
+
+        #[test]
+        fn test_target_probes_around_a_hit() {
+            let mut rng: StdRng = StdRng::seed_from_u64(2);
+            let mut view: TargetView = empty_view();
+            view.set(4, 4, CellView::Hit);
+            view.set(4, 5, CellView::Miss);
+            for _ in 0..20 {
+                let target: (usize, usize) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+                assert!([(3, 4), (5, 4), (4, 3)].contains(&target));
+            }
+
+            let mut view: TargetView = empty_view();
+            view.set(0, 0, CellView::Hit);
+            let target: (usize, usize) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+            assert!([(1, 0), (0, 1)].contains(&target));
+        }
+
+        #[test]
+        fn test_target_follows_the_axis() {
+            let mut rng: StdRng = StdRng::seed_from_u64(3);
+            let mut view: TargetView = empty_view();
+            view.set(4, 4, CellView::Hit);
+            view.set(5, 4, CellView::Hit);
+            for _ in 0..20 {
+                let target: (usize, usize) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+                assert!([(3, 4), (6, 4)].contains(&target));
+            }
+            view.set(3, 4, CellView::Miss);
+            assert_eq!(HuntTarget.choose_target(&view, &mut rng), Some((6, 4)));
+
+            // Sunk ships are finished with and never pull the search back
+            let mut view: TargetView = empty_view();
+            view.set(0, 0, CellView::Sunk(ShipKind::PatrolBoat));
+            view.set(1, 0, CellView::Sunk(ShipKind::PatrolBoat));
+            for _ in 0..20 {
+                let (x_pos, y_pos) = HuntTarget.choose_target(&view, &mut rng).unwrap();
+                assert_eq!((x_pos + y_pos) % 2, 0);
+            }
+            assert_eq!(
+                HuntTarget.choose_target(&TargetView::new(1, 1, Vec::new()), &mut rng),
+                Some((0, 0))
+            );
+            let mut view: TargetView = TargetView::new(1, 1, Vec::new());
+            view.set(0, 0, CellView::Miss);
+            assert_eq!(HuntTarget.choose_target(&view, &mut rng), None);
+        }
+
+        #[test]
+        fn test_afloat_counts_sunk_ships() {
+            let rules: GameRules = GameRules::with_preset(10, 10, FleetPreset::Russian, 8).unwrap();
+            let mut view: TargetView = TargetView::new(10, 10, rules.fleet.clone());
+            assert_eq!(view.afloat(), rules.fleet);
+            view.set(0, 0, CellView::Sunk(ShipKind::Submarine));
+            view.set(2, 0, CellView::Sunk(ShipKind::Submarine));
+            view.set(4, 0, CellView::Sunk(ShipKind::Destroyer));
+            let afloat: Vec<(ShipKind, usize)> = view.afloat();
+            assert_eq!(afloat.len(), rules.fleet.len() - 2);
+            let count = |kind: ShipKind| -> usize {
+                afloat
+                    .iter()
+                    .filter(|(afloat_kind, _)| *afloat_kind == kind)
+                    .count()
+            };
+            assert_eq!(count(ShipKind::Submarine), 2);
+            assert_eq!(count(ShipKind::Destroyer), 3);
+        }
+
+        // Average number of shots a strategy needs to sink a classic fleet
+        fn average_shots(strategy: &dyn Strategy) -> usize {
+            let rules: GameRules = GameRules::default();
+            let mut total: usize = 0;
+            for seed in 0..20 {
+                let mut rng: StdRng = StdRng::seed_from_u64(seed);
+                let mut board: Board = Board::new(2, rules.clone()).unwrap();
+                board.ship_set = ShipSet::new_with_rng(2, &rules, &mut rng).unwrap();
+                board.start_board();
+                let mut shots: usize = 0;
+                while !board.fleet_sunk(1) {
+                    let view: TargetView = TargetView::from_board(&board, 1);
+                    let (x_pos, y_pos) = strategy.choose_target(&view, &mut rng).unwrap();
+                    assert_eq!(view.get(x_pos, y_pos), Some(CellView::Unknown));
+                    board.fire(x_pos, y_pos, 1).unwrap();
+                    shots += 1;
+                }
+                assert!(shots <= 100);
+                total += shots;
+            }
+            total / 20
+        }
+
+        #[test]
+        fn test_density_favours_crowded_cells() {
+            let mut rng: StdRng = StdRng::seed_from_u64(4);
+            let view: TargetView = empty_view();
+            let density: Vec<usize> = Density::density(&view);
+            let (x_pos, y_pos) = Density.choose_target(&view, &mut rng).unwrap();
+            assert!(density[x_pos * 10 + y_pos] > density[0]);
+            assert!((3..7).contains(&x_pos) && (3..7).contains(&y_pos));
+
+            // A column of misses leaves the corner a single upright placement
+            let mut view: TargetView = TargetView::new(10, 10, vec![(ShipKind::PatrolBoat, 2)]);
+            for y_pos in 0..10 {
+                view.set(1, y_pos, CellView::Miss);
+            }
+            assert_eq!(Density::density(&view)[0], 1);
+            view.set(0, 1, CellView::Miss);
+            assert_eq!(Density::density(&view)[0], 0);
+        }
+
+        #[test]
+        fn test_density_finishes_hit_ships() {
+            let mut rng: StdRng = StdRng::seed_from_u64(5);
+            let mut view: TargetView = empty_view();
+            view.set(4, 4, CellView::Hit);
+            view.set(5, 4, CellView::Hit);
+            for _ in 0..20 {
+                let target: (usize, usize) = Density.choose_target(&view, &mut rng).unwrap();
+                assert!([(3, 4), (6, 4)].contains(&target));
+            }
+        }
+
+        #[test]
+        fn test_density_survives_long_hit_ships() {
+            let mut rng: StdRng = StdRng::seed_from_u64(6);
+            let mut view: TargetView = TargetView::new(26, 26, vec![(ShipKind::Carrier, 26)]);
+            for x_pos in 0..25 {
+                view.set(x_pos, 0, CellView::Hit);
+            }
+            assert_eq!(Density::density(&view)[25 * 26], usize::MAX);
+            assert_eq!(Density.choose_target(&view, &mut rng), Some((25, 0)));
+        }
+
+        #[test]
+        fn test_difficulties_rank_by_strength() {
+            let easy: usize = average_shots(Difficulty::Easy.strategy().as_ref());
+            let medium: usize = average_shots(Difficulty::Medium.strategy().as_ref());
+            let hard: usize = average_shots(Difficulty::Hard.strategy().as_ref());
+            assert!(
+                hard < medium && medium < easy,
+                "{} {} {}",
+                easy,
+                medium,
+                hard
+            );
+            // Random fire needs around 95 shots on average, hunt and target far fewer
+            assert!(medium < 75);
+            assert_eq!(average_shots(&RandomFire), easy);
+        }
+    }
+}
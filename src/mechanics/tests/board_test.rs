@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    mod board {
+        use mechanics::board::{Board, CompactBoard, PositionVectors};
+        use mechanics::position::{FireOutcome, FiredState};
+        use mechanics::rules::GameRules;
+        use mechanics::ship::{Ship, ShipKind};
+
+        fn started_board() -> Board {
+            let mut board: Board = Board::new(2, GameRules::default()).unwrap();
+            board.place_fleet(0, Ship::new_ships()).unwrap();
+            board.place_fleet(1, Ship::new_ships()).unwrap();
+            board.start_board();
+            board
+        }
+
+        #[test]
+        fn test_fire_hit_and_miss() {
+            let mut board: Board = started_board();
+            assert_eq!(
+                board.fire(0, 0, 1).unwrap(),
+                FireOutcome::Hit {
+                    kind: ShipKind::Carrier
+                }
+            );
+            assert!(board.positions[0][0].fired_state[1] == FiredState::Hit);
+            assert!(board.positions[0][0].fired_state[0] == FiredState::Untouched);
+
+            assert_eq!(board.fire(9, 9, 1).unwrap(), FireOutcome::Miss);
+            assert!(board.positions[9][9].fired_state[1] == FiredState::Miss);
+            assert_eq!(board.fire(9, 9, 1).unwrap(), FireOutcome::AlreadyFired);
+            assert!(board.fire(10, 9, 1).is_err());
+            assert!(board.fire(9, 9, 2).is_err());
+        }
+
+        #[test]
+        fn test_fire_sinks_ship() {
+            let mut board: Board = started_board();
+            board.fire(0, 4, 1).unwrap();
+            assert_eq!(
+                board.fire(1, 4, 1).unwrap(),
+                FireOutcome::Sunk {
+                    kind: ShipKind::PatrolBoat
+                }
+            );
+            assert!(board.positions[0][4].fired_state[1] == FiredState::Sunk(ShipKind::PatrolBoat));
+            assert!(board.positions[1][4].fired_state[1] == FiredState::Sunk(ShipKind::PatrolBoat));
+
+            assert_eq!(board.fire(1, 4, 1).unwrap(), FireOutcome::AlreadyFired);
+            assert!(board.positions[1][4].fired_state[1] == FiredState::Sunk(ShipKind::PatrolBoat));
+        }
+
+        #[test]
+        fn test_fire_eliminates_player() {
+            let mut board: Board = started_board();
+            let cells: Vec<(usize, usize)> = Ship::new_ships()
+                .into_iter()
+                .flat_map(|ship: Ship| ship.location)
+                .collect::<Vec<(usize, usize)>>();
+            let (last_x, last_y) = cells[cells.len() - 1];
+            for (x_pos, y_pos) in cells[..cells.len() - 1].iter() {
+                board.fire(*x_pos, *y_pos, 1).unwrap();
+            }
+            assert!(!board.fleet_sunk(1));
+            assert_eq!(
+                board.fire(last_x, last_y, 1).unwrap(),
+                FireOutcome::PlayerEliminated {
+                    kind: ShipKind::PatrolBoat
+                }
+            );
+            assert!(board.fleet_sunk(1));
+            assert!(!board.fleet_sunk(0));
+        }
+
+        #[test]
+        fn test_compact_board_round_trip() {
+            let mut board: Board = started_board();
+            board.fire(0, 4, 1).unwrap();
+            board.fire(1, 4, 1).unwrap();
+            board.fire(0, 0, 1).unwrap();
+            board.fire(9, 9, 0).unwrap();
+            let personal: PositionVectors = board.get_board_with_player_positions(1);
+            let compact: CompactBoard = CompactBoard::from_positions(&personal);
+            assert_eq!(compact.to_positions(), personal);
+            assert_eq!(
+                compact.state(1, 1, 4),
+                FiredState::Sunk(ShipKind::PatrolBoat)
+            );
+            assert_eq!(compact.state(1, 0, 0), FiredState::Hit);
+            assert_eq!(compact.state(1, 2, 0), FiredState::Ship(ShipKind::Carrier));
+            assert_eq!(compact.state(0, 9, 9), FiredState::Miss);
+            assert_eq!(compact.players[1].fired().count(), 3);
+
+            let waiting: Board = Board::new(3, GameRules::default()).unwrap();
+            let compact: CompactBoard = CompactBoard::from_positions(&waiting.positions);
+            assert_eq!(compact.to_positions(), waiting.positions);
+            assert_eq!(compact.state(2, 5, 5), FiredState::Empty);
+        }
+
+        #[test]
+        fn test_board_storage_is_compact() {
+            let mut board: Board = Board::new(8, GameRules::default()).unwrap();
+            board.start_board();
+            board.fire(3, 3, 7).unwrap();
+            let stored: String = serde_json::to_string(&board).unwrap();
+            let legacy: String = serde_json::to_string(&board.positions).unwrap();
+            assert!(stored.len() * 4 < legacy.len());
+
+            let restored: Board = serde_json::from_str(&stored).unwrap();
+            assert_eq!(restored.positions, board.positions);
+
+            let mut value: serde_json::Value = serde_json::from_str(&stored).unwrap();
+            value["positions"] = serde_json::from_str(&legacy).unwrap();
+            let restored: Board = serde_json::from_value(value).unwrap();
+            assert_eq!(restored.positions, board.positions);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    mod event {
+        use mechanics::event::GameEvent;
+        use mechanics::game::{Game, GamePhase};
+        use mechanics::position::{FireOutcome, Target};
+        use mechanics::rules::{GameRules, TurnMode};
+        use mechanics::ship::{Ship, ShipKind};
+
+        // Every cell above the default layout, which only covers rows 0 to 4
+        fn water(to: usize) -> impl Iterator<Item = Target> {
+            (5..10).flat_map(move |y_pos: usize| {
+                (0..10).map(move |x_pos: usize| Target::new(to, x_pos, y_pos))
+            })
+        }
+
+        fn played_game(rules: GameRules) -> Game {
+            let mut game: Game = Game::new_with_seed(2, 7, rules, 2024).unwrap();
+            game.join("player_0".to_string()).unwrap();
+            game.join("player_1".to_string()).unwrap();
+            game.place_fleet(0, Ship::new_ships()).unwrap();
+            game.place_fleet(1, Ship::new_ships()).unwrap();
+            let challenge: String = game.new_challenge();
+            game.start(challenge).unwrap();
+            // Player 0 sinks the whole fleet and spends any leftover salvo on
+            // water, while player 1 only hits water
+            let mut cells = Ship::new_ships()
+                .into_iter()
+                .flat_map(|ship: Ship| ship.location)
+                .map(|(x_pos, y_pos)| Target::new(1, x_pos, y_pos))
+                .chain(water(1));
+            let mut misses = water(0);
+            while !game.is_finished() {
+                while game.shots_remaining(0) > 0 {
+                    let salvo: Vec<Target> = cells
+                        .by_ref()
+                        .take(game.shots_remaining(0))
+                        .collect::<Vec<Target>>();
+                    game.fire_salvo(0, &salvo).unwrap();
+                }
+                if game.shots_remaining(1) > 0 {
+                    let salvo: Vec<Target> = misses
+                        .by_ref()
+                        .take(game.shots_remaining(1))
+                        .collect::<Vec<Target>>();
+                    game.fire_salvo(1, &salvo).unwrap();
+                }
+                assert!(game.advance_round());
+            }
+            game
+        }
+
+        #[test]
+        fn test_log_records_the_game() {
+            let game: Game = played_game(GameRules::default());
+            let events: &[GameEvent] = game.events();
+            assert!(matches!(
+                events[0],
+                GameEvent::GameCreated {
+                    number_of_players: 2,
+                    game_number: 7,
+                    seed: 2024,
+                    ..
+                }
+            ));
+            assert_eq!(
+                events[1],
+                GameEvent::PlayerJoined {
+                    player_id: "player_0".to_string()
+                }
+            );
+            assert!(events.contains(&GameEvent::ShotFired {
+                from: 0,
+                shot: 0,
+                target: Target::new(1, 0, 0),
+                outcome: FireOutcome::Hit {
+                    kind: ShipKind::Carrier
+                },
+            }));
+            assert!(events.contains(&GameEvent::ShipSunk {
+                player: 1,
+                kind: ShipKind::PatrolBoat
+            }));
+            assert!(events.contains(&GameEvent::PlayerEliminated { player: 1 }));
+            assert_eq!(
+                events.last(),
+                Some(&GameEvent::GameFinished { winners: vec![0] })
+            );
+        }
+
+        #[test]
+        fn test_replay_rebuilds_the_game() {
+            let rules: GameRules = GameRules {
+                salvo: true,
+                turn_mode: TurnMode::Alternating { chain_on_hit: true },
+                ..GameRules::default()
+            };
+            for rules in [GameRules::default(), rules] {
+                let mut game: Game = played_game(rules);
+                let events: Vec<GameEvent> = game.take_events();
+                assert!(game.events().is_empty());
+                let replayed: Game = Game::replay(&events).unwrap();
+                assert_eq!(replayed.phase, game.phase);
+                assert_eq!(replayed.boards.positions, game.boards.positions);
+                assert_eq!(replayed.challenge, game.challenge);
+                assert_eq!(replayed.events(), &events[..]);
+            }
+        }
+
+        #[test]
+        fn test_replay_rejects_tampered_logs() {
+            let mut game: Game = played_game(GameRules::default());
+            let events: Vec<GameEvent> = game.take_events();
+            assert!(Game::replay(&events[1..]).is_err());
+            assert!(Game::replay(&events[..events.len() - 1]).is_err());
+
+            let mut tampered: Vec<GameEvent> = events.clone();
+            let shot: usize = tampered
+                .iter()
+                .position(|event: &GameEvent| matches!(event, GameEvent::ShotFired { .. }))
+                .unwrap();
+            if let GameEvent::ShotFired { outcome, .. } = &mut tampered[shot] {
+                *outcome = FireOutcome::Miss;
+            }
+            assert!(Game::replay(&tampered).is_err());
+
+            let mut reordered: Vec<GameEvent> = events.clone();
+            let started: usize = reordered
+                .iter()
+                .position(|event: &GameEvent| matches!(event, GameEvent::GameStarted { .. }))
+                .unwrap();
+            reordered.swap(started - 1, started);
+            assert!(Game::replay(&reordered).is_err());
+            assert!(matches!(
+                Game::replay(&events).unwrap().phase,
+                GamePhase::Finished { .. }
+            ));
+        }
+    }
+}
@@ -0,0 +1,452 @@
+#[cfg(test)]
+mod tests {
+    mod game {
+        use mechanics::ai::Difficulty;
+        use mechanics::event::GameEvent;
+        use mechanics::game::{Game, GamePhase};
+        use mechanics::position::{FireError, FireOutcome, FiredState, Target};
+        use mechanics::rules::{GameRules, TurnMode};
+        use mechanics::ship::{Ship, ShipKind};
+
+        fn started_game(number_of_players: usize) -> Game {
+            started_game_with_rules(number_of_players, GameRules::default())
+        }
+
+        fn started_game_with_rules(number_of_players: usize, rules: GameRules) -> Game {
+            let mut game: Game = Game::new(number_of_players, 1, rules).unwrap();
+            for index in 0..number_of_players {
+                game.join(format!("player_{index}")).unwrap();
+            }
+            for index in 0..number_of_players {
+                game.place_fleet(index, Ship::new_ships()).unwrap();
+            }
+            game.start("challenge".to_string()).unwrap();
+            game
+        }
+
+        fn fleet_cells() -> Vec<(usize, usize)> {
+            Ship::new_ships()
+                .into_iter()
+                .flat_map(|ship: Ship| ship.location)
+                .collect::<Vec<(usize, usize)>>()
+        }
+
+        // Every cell above the default layout, which only covers rows 0 to 4
+        fn miss_cells() -> Vec<(usize, usize)> {
+            (5..10)
+                .flat_map(|y_pos: usize| (0..10).map(move |x_pos: usize| (x_pos, y_pos)))
+                .collect::<Vec<(usize, usize)>>()
+        }
+
+        #[test]
+        fn test_phase_transitions() {
+            let mut game: Game = Game::new(2, 1, GameRules::default()).unwrap();
+            assert_eq!(game.phase, GamePhase::Lobby);
+            assert!(game.fire(0, 1, 0, 0).is_err());
+            assert!(game.place_fleet(0, Ship::new_ships()).is_err());
+            game.join("player_0".to_string()).unwrap();
+            assert!(game.join("player_0".to_string()).is_err());
+            game.join("player_1".to_string()).unwrap();
+            assert_eq!(game.phase, GamePhase::Placement);
+            assert!(game.join("player_2".to_string()).is_err());
+            assert!(game.fire(0, 1, 0, 0).is_err());
+            game.place_fleet(0, Ship::new_ships()).unwrap();
+            assert!(game.start("challenge".to_string()).is_err());
+            game.place_fleet(1, Ship::new_ships()).unwrap();
+            game.start("challenge".to_string()).unwrap();
+            assert_eq!(game.phase, GamePhase::InProgress { round: 1 });
+            assert!(game.place_fleet(1, Ship::new_ships()).is_err());
+            assert!(game.transition(GamePhase::Lobby).is_err());
+            assert!(game.transition(GamePhase::InProgress { round: 3 }).is_err());
+        }
+
+        #[test]
+        fn test_round_progression() {
+            let mut game: Game = started_game(3);
+            game.fire(0, 1, 9, 9).unwrap();
+            assert!(game.fire(0, 2, 9, 8).is_err());
+            assert!(!game.advance_round());
+            game.fire(1, 2, 9, 9).unwrap();
+            game.fire(2, 0, 9, 9).unwrap();
+            assert!(game.advance_round());
+            assert_eq!(game.shots_remaining, vec![1, 1, 1]);
+            assert_eq!(game.phase, GamePhase::InProgress { round: 2 });
+        }
+
+        #[test]
+        fn test_elimination_and_win() {
+            let mut game: Game = started_game(3);
+            let cells: Vec<(usize, usize)> = fleet_cells();
+            let misses: Vec<(usize, usize)> = miss_cells();
+            for (index, (x_pos, y_pos)) in cells.iter().enumerate() {
+                game.fire(0, 2, *x_pos, *y_pos).unwrap();
+                game.fire(1, 2, misses[index].0, misses[index].1).unwrap();
+                game.fire(2, 0, misses[index].0, misses[index].1).unwrap();
+                assert!(game.advance_round());
+            }
+            assert!(game.is_eliminated(2));
+            assert_eq!(game.shots_per_round(2), 0);
+            assert!(game.fire(2, 0, 8, 8).is_err());
+            assert!(matches!(game.phase, GamePhase::InProgress { .. }));
+
+            for (index, (x_pos, y_pos)) in cells.iter().enumerate() {
+                game.fire(0, 1, *x_pos, *y_pos).unwrap();
+                let (miss_x, miss_y) = misses[cells.len() + index];
+                game.fire(1, 0, miss_x, miss_y).unwrap();
+                assert!(game.advance_round());
+            }
+            assert!(game.is_eliminated(1));
+            assert_eq!(game.phase, GamePhase::Finished { winners: vec![0] });
+            assert!(game.fire(0, 1, 8, 8).is_err());
+        }
+
+        #[test]
+        fn test_simultaneous_elimination_is_a_tie() {
+            let mut game: Game = started_game(2);
+            for (x_pos, y_pos) in fleet_cells().iter() {
+                game.fire(0, 1, *x_pos, *y_pos).unwrap();
+                game.fire(1, 0, *x_pos, *y_pos).unwrap();
+                assert!(game.advance_round());
+            }
+            assert_eq!(
+                game.phase,
+                GamePhase::Finished {
+                    winners: vec![0, 1]
+                }
+            );
+        }
+
+        #[test]
+        fn test_salvo_shots_follow_surviving_ships() {
+            let rules: GameRules = GameRules {
+                salvo: true,
+                ..GameRules::default()
+            };
+            let mut game: Game = started_game_with_rules(2, rules);
+            assert_eq!(game.shots_remaining, vec![5, 5]);
+            assert_eq!(
+                game.fire_salvo(0, &[Target::new(1, 5, 5), Target::new(1, 5, 5)]),
+                Err(FireError::AlreadyFired(Target::new(1, 5, 5)))
+            );
+            let too_many: Vec<Target> = (0..6)
+                .map(|index: usize| Target::new(1, index, 9))
+                .collect::<Vec<Target>>();
+            assert!(game.fire_salvo(0, &too_many).is_err());
+            assert!(game
+                .fire_salvo(0, &[Target::new(1, 0, 4), Target::new(1, 10, 0)])
+                .is_err());
+            assert_eq!(game.shots_remaining(0), 5);
+
+            let outcomes: Vec<FireOutcome> = game
+                .fire_salvo(0, &[Target::new(1, 0, 4), Target::new(1, 1, 4)])
+                .unwrap();
+            assert_eq!(
+                outcomes,
+                vec![
+                    FireOutcome::Hit {
+                        kind: ShipKind::PatrolBoat
+                    },
+                    FireOutcome::Sunk {
+                        kind: ShipKind::PatrolBoat
+                    }
+                ]
+            );
+            assert_eq!(game.shots_remaining(0), 3);
+            let misses: Vec<Target> = (0..3)
+                .map(|index: usize| Target::new(1, index, 9))
+                .collect::<Vec<Target>>();
+            game.fire_salvo(0, &misses).unwrap();
+            assert!(!game.advance_round());
+            let misses: Vec<Target> = (0..5)
+                .map(|index: usize| Target::new(0, index, 9))
+                .collect::<Vec<Target>>();
+            game.fire_salvo(1, &misses).unwrap();
+            assert!(game.advance_round());
+            assert_eq!(game.shots_remaining, vec![5, 4]);
+        }
+
+        #[test]
+        fn test_salvo_shots_capped_by_open_cells() {
+            let rules: GameRules = GameRules {
+                salvo: true,
+                ..GameRules::default()
+            };
+            let mut game: Game = started_game_with_rules(2, rules);
+            for x_pos in 0..10 {
+                for y_pos in 0..10 {
+                    if y_pos != 9 || 2 <= x_pos {
+                        game.boards.positions[x_pos][y_pos].fired_state[1] = FiredState::Miss;
+                    }
+                }
+            }
+            assert_eq!(game.open_cells(0), 2);
+            assert_eq!(game.shots_per_round(0), 2);
+            game.fire(0, 1, 0, 9).unwrap();
+            assert_eq!(game.shots_remaining(0), 1);
+            assert_eq!(game.shots_remaining(1), 5);
+        }
+
+        #[test]
+        fn test_alternating_turns() {
+            let rules: GameRules = GameRules {
+                turn_mode: TurnMode::Alternating {
+                    chain_on_hit: false,
+                },
+                ..GameRules::default()
+            };
+            let mut game: Game = started_game_with_rules(3, rules);
+            let first: usize = game.on_move.unwrap();
+            let (second, third) = ((first + 1) % 3, (first + 2) % 3);
+            let mut expected: Vec<usize> = vec![0; 3];
+            expected[first] = 1;
+            assert_eq!(game.shots_remaining, expected);
+            assert!(game.fire(second, first, 9, 9).is_err());
+            game.fire(first, second, 0, 0).unwrap();
+            assert!(game.fire(first, second, 1, 0).is_err());
+            assert!(game.advance_round());
+            assert_eq!(game.on_move, Some(second));
+            game.fire(second, third, 9, 9).unwrap();
+            assert!(game.advance_round());
+            game.fire(third, first, 9, 9).unwrap();
+            assert!(game.advance_round());
+            assert_eq!(game.on_move, Some(first));
+            assert_eq!(game.turn_state().shots_remaining, expected);
+        }
+
+        #[test]
+        fn test_chain_on_hit() {
+            let rules: GameRules = GameRules {
+                turn_mode: TurnMode::Alternating { chain_on_hit: true },
+                ..GameRules::default()
+            };
+            let mut game: Game = started_game_with_rules(2, rules);
+            let first: usize = game.on_move.unwrap();
+            let second: usize = 1 - first;
+            game.fire(first, second, 0, 0).unwrap();
+            assert_eq!(game.shots_remaining(first), 1);
+            assert!(!game.advance_round());
+            game.fire(first, second, 9, 9).unwrap();
+            assert_eq!(game.shots_remaining(first), 0);
+            assert!(game.advance_round());
+            assert_eq!(game.on_move, Some(second));
+            game.fire(second, first, 9, 9).unwrap();
+            assert!(game.advance_round());
+            assert_eq!(game.on_move, Some(first));
+        }
+
+        #[test]
+        fn test_seed_replays_game_randomness() {
+            let rules: GameRules = GameRules {
+                turn_mode: TurnMode::Alternating {
+                    chain_on_hit: false,
+                },
+                ..GameRules::default()
+            };
+            let seeded = |seed: u64| -> Game {
+                let mut game: Game = Game::new_with_seed(4, 1, rules.clone(), seed).unwrap();
+                for index in 0..4 {
+                    game.join(format!("player_{index}")).unwrap();
+                }
+                for index in 0..4 {
+                    let ships: Vec<Ship> = game.boards.ship_set[index].clone();
+                    game.place_fleet(index, ships).unwrap();
+                }
+                let challenge: String = game.new_challenge();
+                game.start(challenge).unwrap();
+                game
+            };
+            let game: Game = seeded(42);
+            let replay: Game = seeded(42);
+            assert_eq!(game.boards.ship_set, replay.boards.ship_set);
+            assert_eq!(game.challenge, replay.challenge);
+            assert_eq!(game.challenge.len(), 10);
+            assert_eq!(game.on_move, replay.on_move);
+
+            let restored: Game =
+                serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+            assert_eq!(restored.seed, 42);
+            assert_eq!(restored.new_challenge(), game.challenge);
+
+            let other: Game = seeded(43);
+            assert_ne!(game.boards.ship_set, other.boards.ship_set);
+            assert_ne!(game.challenge, other.challenge);
+        }
+
+        #[test]
+        fn test_new_game_checks_rules() {
+            let huge: GameRules = GameRules {
+                width: 100_000,
+                height: 100_000,
+                ..GameRules::default()
+            };
+            assert!(Game::new_with_seed(2, 1, huge, 1).is_err());
+            let crowded: GameRules = GameRules {
+                max_players: 40,
+                ..GameRules::default()
+            };
+            assert!(Game::new_with_seed(32, 1, crowded, 1).is_err());
+            assert!(Game::new_with_seed(9, 1, GameRules::default(), 1).is_err());
+        }
+
+        #[test]
+        fn test_fire_errors() {
+            let mut game: Game = Game::new(2, 1, GameRules::default()).unwrap();
+            assert_eq!(game.fire(0, 1, 0, 0), Err(FireError::NotInProgress));
+            let mut game: Game = started_game(3);
+            assert_eq!(game.fire(3, 1, 0, 0), Err(FireError::UnknownPlayer(3)));
+            assert_eq!(game.fire(0, 3, 0, 0), Err(FireError::UnknownTarget(3)));
+            assert_eq!(game.fire(0, 0, 0, 0), Err(FireError::SelfTarget(0)));
+            assert_eq!(
+                game.fire(0, 1, 10, 0),
+                Err(FireError::OutOfBounds { lon: 10, lat: 0 })
+            );
+            assert_eq!(
+                game.fire(0, 1, 0, usize::MAX),
+                Err(FireError::OutOfBounds {
+                    lon: 0,
+                    lat: usize::MAX
+                })
+            );
+            assert_eq!(game.fire_salvo(0, &[]), Err(FireError::EmptySalvo));
+            game.fire(0, 1, 0, 0).unwrap();
+            assert_eq!(
+                game.fire(0, 1, 1, 0),
+                Err(FireError::NoShotsRemaining {
+                    requested: 1,
+                    remaining: 0
+                })
+            );
+            assert_eq!(
+                game.fire(2, 1, 0, 0),
+                Err(FireError::AlreadyFired(Target::new(1, 0, 0)))
+            );
+
+            let mut game: Game = started_game(3);
+            let misses: Vec<(usize, usize)> = miss_cells();
+            for (index, (x_pos, y_pos)) in fleet_cells().iter().enumerate() {
+                game.fire(0, 2, *x_pos, *y_pos).unwrap();
+                game.fire(1, 0, misses[index].0, misses[index].1).unwrap();
+                game.fire(2, 1, misses[index].0, misses[index].1).unwrap();
+                assert!(game.advance_round());
+            }
+            assert!(game.is_eliminated(2));
+            assert_eq!(game.fire(2, 0, 9, 9), Err(FireError::Eliminated(2)));
+            assert_eq!(game.fire(0, 2, 9, 9), Err(FireError::TargetEliminated(2)));
+        }
+
+        fn bot_game(rules: GameRules, seed: u64) -> Game {
+            let mut game: Game = Game::new_with_seed(4, 1, rules, seed).unwrap();
+            game.difficulty = Difficulty::Hard;
+            for index in 1..4 {
+                game.join_bot(format!("bot_{index}")).unwrap();
+            }
+            assert_eq!(game.phase, GamePhase::Lobby);
+            game.join("player_0".to_string()).unwrap();
+            game.place_bot_fleets().unwrap();
+            assert!(!game.all_fleets_placed());
+            let ships: Vec<Ship> = game.boards.ship_set[3].clone();
+            game.place_fleet(3, ships).unwrap();
+            let challenge: String = game.new_challenge();
+            game.start(challenge).unwrap();
+            game
+        }
+
+        // The human only ever fires into the water of the first opponent left
+        fn human_miss(game: &Game) -> Target {
+            (0..3)
+                .filter(|index: &usize| !game.is_eliminated(*index))
+                .flat_map(|to: usize| {
+                    (0..10)
+                        .flat_map(move |x_pos: usize| {
+                            (0..10).map(move |y_pos: usize| (x_pos, y_pos))
+                        })
+                        .map(move |(x_pos, y_pos)| Target::new(to, x_pos, y_pos))
+                })
+                .find(|target: &Target| {
+                    game.boards.positions[target.lon][target.lat].fired_state[target.to]
+                        == FiredState::Untouched
+                        && !game.boards.ship_set[target.to]
+                            .iter()
+                            .any(|ship: &Ship| ship.location.contains(&(target.lon, target.lat)))
+                })
+                .unwrap()
+        }
+
+        #[test]
+        fn test_bots_fill_the_round() {
+            let mut game: Game = bot_game(GameRules::default(), 5);
+            assert_eq!(game.player_tags[3], "player_0");
+            assert!(game.is_bot(0) && !game.is_bot(3));
+            let shots: Vec<(usize, Target, FireOutcome)> = game.play_bots();
+            assert_eq!(
+                shots
+                    .iter()
+                    .map(|(from, _, _)| *from)
+                    .collect::<Vec<usize>>(),
+                vec![0, 1, 2]
+            );
+            assert!(shots.iter().all(|(from, target, _)| *from != target.to));
+            assert_eq!(game.shots_remaining, vec![0, 0, 0, 1]);
+            assert!(game.play_bots().is_empty());
+
+            let target: Target = human_miss(&game);
+            game.fire(3, target.to, target.lon, target.lat).unwrap();
+            assert!(game.advance_round());
+            assert_eq!(game.play_bots().len(), 3);
+            assert_eq!(game.phase, GamePhase::InProgress { round: 2 });
+        }
+
+        #[test]
+        fn test_bots_fire_on_sunk_fleets_last() {
+            let mut game: Game = bot_game(GameRules::default(), 5);
+            let sink = |game: &mut Game, to: usize| {
+                for ship in game.boards.ship_set[to].clone() {
+                    for (x_pos, y_pos) in ship.location {
+                        game.boards.fire(x_pos, y_pos, to).unwrap();
+                    }
+                }
+                assert!(game.boards.fleet_sunk(to) && !game.is_eliminated(to));
+            };
+            sink(&mut game, 1);
+            sink(&mut game, 2);
+            let targets: Vec<Target> = game.bot_salvo(0);
+            assert_eq!(targets.len(), 1);
+            assert_eq!(targets[0].to, 3);
+
+            // Until the round ends, the sunk fleets still have to take the shot
+            sink(&mut game, 3);
+            let targets: Vec<Target> = game.bot_salvo(0);
+            assert_eq!(targets.len(), 1);
+            assert_ne!(targets[0].to, 0);
+            assert_eq!(game.play_bots().len(), 3);
+        }
+
+        #[test]
+        fn test_bots_play_a_game_out() {
+            let rules: GameRules = GameRules {
+                turn_mode: TurnMode::Alternating { chain_on_hit: true },
+                ..GameRules::default()
+            };
+            for rules in [GameRules::default(), rules] {
+                let mut game: Game = bot_game(rules.clone(), 11);
+                let mut replay: Game = bot_game(rules, 11);
+                game.play_bots();
+                while !game.is_finished() {
+                    assert!(game.shots_remaining(3) > 0);
+                    let target: Target = human_miss(&game);
+                    game.fire(3, target.to, target.lon, target.lat).unwrap();
+                    game.advance_round();
+                    game.play_bots();
+                }
+                assert!(game.is_eliminated(3));
+                let events: Vec<GameEvent> = game.take_events();
+                assert!(Game::replay(&events).is_ok());
+
+                // The same seed drives the bots to the same shots
+                replay.play_bots();
+                let first: Vec<GameEvent> = replay.take_events();
+                assert_eq!(first[..], events[..first.len()]);
+            }
+        }
+    }
+}
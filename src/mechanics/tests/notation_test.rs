@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    mod notation {
+        use mechanics::event::GameEvent;
+        use mechanics::game::Game;
+        use mechanics::notation;
+        use mechanics::position::Target;
+        use mechanics::rules::{GameRules, TurnMode};
+        use mechanics::ship::{FleetPreset, Ship};
+
+        // Player 0 sinks player 1's fleet before firing anywhere else, while
+        // player 1 only ever hits water
+        fn played_events(rules: GameRules) -> Vec<GameEvent> {
+            let mut game: Game = Game::new_with_seed(2, 3, rules, 99).unwrap();
+            game.join("player_0".to_string()).unwrap();
+            game.join("player 1".to_string()).unwrap();
+            let fleets: Vec<Vec<Ship>> = game.boards.ship_set.clone();
+            game.place_fleet(0, fleets[0].clone()).unwrap();
+            game.place_fleet(1, fleets[1].clone()).unwrap();
+            let challenge: String = game.new_challenge();
+            game.start(challenge).unwrap();
+            let cells = |to: usize| -> Vec<Target> {
+                (0..10)
+                    .flat_map(|y_pos: usize| (0..10).map(move |x_pos: usize| (x_pos, y_pos)))
+                    .map(|(x_pos, y_pos)| Target::new(to, x_pos, y_pos))
+                    .collect::<Vec<Target>>()
+            };
+            let mut hits: Vec<Target> = fleets[1]
+                .iter()
+                .flat_map(|ship: &Ship| ship.location.clone())
+                .map(|(x_pos, y_pos)| Target::new(1, x_pos, y_pos))
+                .collect::<Vec<Target>>();
+            let water: Vec<Target> = cells(1)
+                .into_iter()
+                .filter(|target: &Target| !hits.contains(target))
+                .collect::<Vec<Target>>();
+            hits.extend(water);
+            let misses: Vec<Target> = cells(0)
+                .into_iter()
+                .filter(|target: &Target| {
+                    !fleets[0]
+                        .iter()
+                        .any(|ship: &Ship| ship.location.contains(&(target.lon, target.lat)))
+                })
+                .collect::<Vec<Target>>();
+            let (mut hits, mut misses) = (hits.into_iter(), misses.into_iter());
+            while !game.is_finished() {
+                while game.shots_remaining(0) > 0 {
+                    let salvo: Vec<Target> = hits
+                        .by_ref()
+                        .take(game.shots_remaining(0))
+                        .collect::<Vec<Target>>();
+                    game.fire_salvo(0, &salvo).unwrap();
+                }
+                if game.shots_remaining(1) > 0 {
+                    let salvo: Vec<Target> = misses
+                        .by_ref()
+                        .take(game.shots_remaining(1))
+                        .collect::<Vec<Target>>();
+                    game.fire_salvo(1, &salvo).unwrap();
+                }
+                assert!(game.advance_round());
+            }
+            game.take_events()
+        }
+
+        fn first_line<'a>(record: &'a str, prefix: &str) -> &'a str {
+            record
+                .lines()
+                .find(|line: &&str| line.starts_with(prefix))
+                .unwrap()
+        }
+
+        #[test]
+        fn test_record_round_trips_the_event_log() {
+            let salvo: GameRules = GameRules {
+                salvo: true,
+                turn_mode: TurnMode::Alternating { chain_on_hit: true },
+                no_touching: true,
+                ..GameRules::with_preset(10, 10, FleetPreset::Russian, 8).unwrap()
+            };
+            for rules in [GameRules::default(), salvo] {
+                let events: Vec<GameEvent> = played_events(rules);
+                let record: String = notation::write(&events).unwrap();
+                assert_eq!(notation::read(&record).unwrap(), events);
+                assert!(Game::replay(&notation::read(&record).unwrap()).is_ok());
+            }
+        }
+
+        #[test]
+        fn test_record_is_readable() {
+            let record: String = notation::write(&played_events(GameRules::default())).unwrap();
+            let lines: Vec<&str> = record.lines().collect::<Vec<&str>>();
+            assert_eq!(lines[0], "[Game \"3\"]");
+            assert_eq!(lines[1], "[Seed \"99\"]");
+            assert!(lines.contains(&"[Turns \"simultaneous\"]"));
+            assert!(lines.contains(&"p2 joins player 1"));
+            assert!(first_line(&record, "p1 places Carrier:").split(' ').count() == 7);
+            assert!(first_line(&record, "R1 p2->p1 ").ends_with(" miss"));
+            assert!(first_line(&record, "R1 p1->p2 ").ends_with(" hit"));
+            assert!(lines.contains(&"round 2"));
+            assert!(lines.contains(&"p2 eliminated"));
+            assert_eq!(lines.last(), Some(&"winners p1"));
+        }
+
+        #[test]
+        fn test_read_rejects_bad_records() {
+            let record: String = notation::write(&played_events(GameRules::default())).unwrap();
+            let miss: &str = first_line(&record, "R1 p2->p1 ");
+            let cell: &str = miss.split(' ').nth(2).unwrap();
+            assert!(notation::read("p1 joins alice").is_err());
+            assert!(notation::read(&record.replace("[Seed \"99\"]\n", "")).is_err());
+            assert!(notation::read(&record.replace(miss, "R1 p2->p1 A0 miss")).is_err());
+            assert!(notation::read(&record.replace(miss, &miss.replacen("R1", "R2", 1))).is_err());
+            assert!(notation::read(&record.replace("round 2\n", "round\n")).is_err());
+            // Oversized boards and seats are refused before replay allocates them
+            assert!(notation::read(&record.replace("10x10", "100000x100000")).is_err());
+            let crowded: String = record
+                .replace("[Players \"2\"]", "[Players \"32\"]")
+                .replace("[MaxPlayers \"8\"]", "[MaxPlayers \"40\"]");
+            assert!(notation::read(&crowded).is_err());
+
+            let commented: String = format!("; shared from the server\n{}", record);
+            assert_eq!(notation::read(&commented), notation::read(&record));
+            // A hit needs a ship under it, and a readable record must still replay
+            let forged: String = record.replace(miss, &miss.replace(" miss", " hit"));
+            assert!(notation::read(&forged).is_err());
+            let forged: String = record.replace(miss, &miss.replace(cell, "Z1"));
+            assert!(Game::replay(&notation::read(&forged).unwrap()).is_err());
+        }
+
+        #[test]
+        fn test_write_rejects_unwritable_player_ids() {
+            assert!(notation::is_valid_player_id("player 1"));
+            for player_id in ["", " alice", "alice ", "ali\nce", "ali\rce"] {
+                assert!(!notation::is_valid_player_id(player_id));
+                let mut game: Game = Game::new_with_seed(2, 3, GameRules::default(), 99).unwrap();
+                game.join(player_id.to_string()).unwrap();
+                assert!(notation::write(&game.take_events()).is_err());
+            }
+        }
+    }
+}
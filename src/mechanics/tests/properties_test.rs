@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests {
+    mod properties {
+        use mechanics::board::Board;
+        use mechanics::game::{Game, GamePhase};
+        use mechanics::position::FiredState;
+        use mechanics::rules::GameRules;
+        use mechanics::ship::{FleetPreset, Ship, ShipSet};
+        use proptest::prelude::*;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use std::collections::HashSet;
+
+        fn preset() -> impl Strategy<Value = FleetPreset> {
+            prop::sample::select(FleetPreset::ALL.to_vec())
+        }
+
+        fn started_board(players: usize, seed: u64) -> Board {
+            let rules: GameRules = GameRules::default();
+            let mut board: Board = Board::new(players, rules.clone()).unwrap();
+            board.ship_set =
+                ShipSet::new_with_rng(players, &rules, &mut StdRng::seed_from_u64(seed)).unwrap();
+            board.start_board();
+            board
+        }
+
+        fn ship_cells(board: &Board, player_index: usize) -> HashSet<(usize, usize)> {
+            board.ship_set[player_index]
+                .iter()
+                .flat_map(|ship: &Ship| ship.location.clone())
+                .collect::<HashSet<(usize, usize)>>()
+        }
+
+        proptest! {
+            #[test]
+            fn prop_random_fleets_fit_the_board(
+                seed in any::<u64>(),
+                width in 5usize..=15,
+                height in 5usize..=15,
+                preset in preset(),
+                no_touching in any::<bool>(),
+            ) {
+                let rules: GameRules = match GameRules::with_preset(width, height, preset, 8) {
+                    Ok(rules) => rules,
+                    Err(_) => return Ok(()),
+                };
+                let ships: Vec<Ship> =
+                    match Ship::random_ships(&mut StdRng::seed_from_u64(seed), &rules, no_touching) {
+                        Ok(ships) => ships,
+                        // Spacing the ships out is allowed to fail on crowded boards
+                        Err(error) => {
+                            prop_assert!(no_touching, "{}", error);
+                            return Ok(());
+                        }
+                    };
+                prop_assert!(Ship::validate_fleet(&ships, &rules).is_ok());
+                let mut occupied: HashSet<(usize, usize)> = HashSet::new();
+                for ship in ships.iter() {
+                    for (x_pos, y_pos) in ship.location.iter() {
+                        prop_assert!(rules.in_bounds(*x_pos, *y_pos));
+                        prop_assert!(occupied.insert((*x_pos, *y_pos)));
+                    }
+                }
+            }
+
+            #[test]
+            fn prop_fire_only_changes_the_target_board(
+                seed in any::<u64>(),
+                players in 2usize..=8,
+                shots in prop::collection::vec((0usize..10, 0usize..10, 0usize..8), 1..120),
+            ) {
+                let mut board: Board = started_board(players, seed);
+                for (lon, lat, to) in shots.into_iter() {
+                    let to: usize = to % players;
+                    let before: Board = board.clone();
+                    board.fire(lon, lat, to).unwrap();
+                    for (x_pos, column) in board.positions.iter().enumerate() {
+                        for (y_pos, position) in column.iter().enumerate() {
+                            for player_index in (0..players).filter(|index: &usize| *index != to) {
+                                prop_assert_eq!(
+                                    &position.fired_state[player_index],
+                                    &before.positions[x_pos][y_pos].fired_state[player_index]
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn prop_hits_only_land_on_ships(
+                seed in any::<u64>(),
+                players in 2usize..=8,
+                shots in prop::collection::vec((0usize..10, 0usize..10, 0usize..8), 1..300),
+            ) {
+                let mut board: Board = started_board(players, seed);
+                for (lon, lat, to) in shots.into_iter() {
+                    board.fire(lon, lat, to % players).unwrap();
+                }
+                for player_index in 0..players {
+                    let cells: HashSet<(usize, usize)> = ship_cells(&board, player_index);
+                    let mut hits: usize = 0;
+                    for (x_pos, column) in board.positions.iter().enumerate() {
+                        for (y_pos, position) in column.iter().enumerate() {
+                            match position.fired_state[player_index] {
+                                FiredState::Hit | FiredState::Sunk(_) => {
+                                    prop_assert!(cells.contains(&(x_pos, y_pos)));
+                                    hits += 1;
+                                }
+                                FiredState::Miss => prop_assert!(!cells.contains(&(x_pos, y_pos))),
+                                _ => (),
+                            }
+                        }
+                    }
+                    prop_assert!(hits <= cells.len());
+                }
+            }
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn prop_game_ends_when_one_fleet_remains(seed in any::<u64>(), players in 2usize..=4) {
+                let rules: GameRules = GameRules::default();
+                let fleets: Vec<Vec<Ship>> =
+                    ShipSet::new_with_rng(players, &rules, &mut StdRng::seed_from_u64(seed)).unwrap();
+                let mut game: Game = Game::new(players, 1, rules).unwrap();
+                for index in 0..players {
+                    game.join(format!("player_{index}")).unwrap();
+                }
+                for (index, fleet) in fleets.into_iter().enumerate() {
+                    game.place_fleet(index, fleet).unwrap();
+                }
+                game.start("challenge".to_string()).unwrap();
+
+                // Player 0 works through every enemy ship while everyone else only hits water
+                let mut targets: Vec<(usize, usize, usize)> = (1..players)
+                    .flat_map(|to: usize| {
+                        ship_cells(&game.boards, to)
+                            .into_iter()
+                            .map(move |(x_pos, y_pos)| (to, x_pos, y_pos))
+                    })
+                    .collect::<Vec<(usize, usize, usize)>>();
+                targets.sort_unstable();
+                for (to, x_pos, y_pos) in targets.into_iter() {
+                    prop_assert!(!game.is_finished());
+                    game.fire(0, to, x_pos, y_pos).unwrap();
+                    let shooters: Vec<usize> = (1..players)
+                        .filter(|index: &usize| !game.is_eliminated(*index))
+                        .collect::<Vec<usize>>();
+                    for from in shooters.into_iter() {
+                        // Spread the water shots so player 0's board never runs out of cells
+                        let (to, x_pos, y_pos) = (0..players)
+                            .rev()
+                            .filter(|index: &usize| *index != from && !game.is_eliminated(*index))
+                            .flat_map(|to: usize| {
+                                let cells: HashSet<(usize, usize)> = ship_cells(&game.boards, to);
+                                (0..10)
+                                    .flat_map(move |x_pos: usize| (0..10).map(move |y_pos: usize| (x_pos, y_pos)))
+                                    .filter(move |cell: &(usize, usize)| !cells.contains(cell))
+                                    .map(move |(x_pos, y_pos)| (to, x_pos, y_pos))
+                            })
+                            .find(|(to, x_pos, y_pos)| {
+                                game.boards.positions[*x_pos][*y_pos].fired_state[*to]
+                                    == FiredState::Untouched
+                            })
+                            .unwrap();
+                        game.fire(from, to, x_pos, y_pos).unwrap();
+                    }
+                    prop_assert!(game.advance_round());
+                }
+                prop_assert_eq!(game.phase, GamePhase::Finished { winners: vec![0] });
+            }
+        }
+    }
+}
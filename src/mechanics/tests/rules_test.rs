@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    mod rules {
+        use mechanics::board::Board;
+        use mechanics::position::Position;
+        use mechanics::rules::GameRules;
+        use mechanics::ship::{Ship, ShipKind};
+
+        #[test]
+        fn test_validate() {
+            assert!(GameRules::default().validate(2).is_ok());
+            assert!(GameRules::default().validate(8).is_ok());
+            assert!(GameRules::default().validate(1).is_err());
+            assert!(GameRules::default().validate(9).is_err());
+            assert!(GameRules::new(0, 10, Ship::fleet(), 8).validate(2).is_err());
+            assert!(GameRules::new(27, 10, Ship::fleet(), 8)
+                .validate(2)
+                .is_err());
+            assert!(GameRules::new(4, 4, Ship::fleet(), 8).validate(2).is_err());
+            assert!(GameRules::new(5, 4, Ship::fleet(), 8).validate(2).is_ok());
+            assert!(GameRules::new(10, 10, Vec::new(), 8).validate(2).is_err());
+            let mut fleet: Vec<(ShipKind, usize)> = Ship::fleet();
+            fleet.push((ShipKind::Cruiser, 3));
+            assert!(GameRules::new(10, 10, fleet.clone(), 8).validate(2).is_ok());
+            fleet.push((ShipKind::Cruiser, 2));
+            assert!(GameRules::new(10, 10, fleet, 8).validate(2).is_err());
+            assert!(GameRules::new(10, 10, Ship::fleet(), 32)
+                .validate(2)
+                .is_err());
+        }
+
+        #[test]
+        fn test_board_dimensions() {
+            let rules: GameRules = GameRules::new(12, 7, Ship::fleet(), 4);
+            let mut board: Board = Board::new(3, rules.clone()).unwrap();
+            board.start_board();
+            assert_eq!(board.positions.len(), 12);
+            assert!(board.positions.iter().all(|column| column.len() == 7));
+            assert!(Position::new(11, 6, None, 3, &rules).is_ok());
+            assert!(Position::new(12, 6, None, 3, &rules).is_err());
+            assert!(Position::new(11, 7, None, 3, &rules).is_err());
+        }
+    }
+}
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod tests {
+    mod ship {
+        use mechanics::position::FiredState;
+        use mechanics::rules::GameRules;
+        use mechanics::ship::{FleetPreset, Ship, ShipKind, ShipSet};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use std::collections::HashSet;
+
+        fn ship(kind: ShipKind, location: Vec<(usize, usize)>) -> Ship {
+            Ship { kind, location }
+        }
+
+        #[test]
+        fn test_default_fleet_is_valid() {
+            assert!(Ship::validate_fleet(&Ship::new_ships(), &GameRules::default()).is_ok());
+        }
+
+        #[test]
+        fn test_is_straight() {
+            assert!(ship(ShipKind::Carrier, vec![(3, 4), (4, 4)]).is_straight());
+            assert!(ship(ShipKind::Battleship, vec![(2, 7), (2, 5), (2, 6)]).is_straight());
+            assert!(!ship(ShipKind::Battleship, vec![(2, 5), (2, 6), (2, 8)]).is_straight());
+            assert!(!ship(ShipKind::Battleship, vec![(2, 5), (3, 6), (4, 7)]).is_straight());
+            assert!(!ship(ShipKind::Carrier, Vec::new()).is_straight());
+        }
+
+        #[test]
+        fn test_validate_fleet_rejections() {
+            let mut missing_ship: Vec<Ship> = Ship::new_ships();
+            missing_ship.pop();
+            assert!(Ship::validate_fleet(&missing_ship, &GameRules::default()).is_err());
+
+            let mut wrong_length: Vec<Ship> = Ship::new_ships();
+            wrong_length[0].location.push((2, 0));
+            assert!(Ship::validate_fleet(&wrong_length, &GameRules::default()).is_err());
+
+            let mut out_of_bounds: Vec<Ship> = Ship::new_ships();
+            out_of_bounds[4].location = (6..11).map(|x_pos| (x_pos, 9)).collect();
+            assert!(Ship::validate_fleet(&out_of_bounds, &GameRules::default()).is_err());
+
+            let mut overlapping: Vec<Ship> = Ship::new_ships();
+            overlapping[4].location = vec![(0, 1), (1, 1)];
+            assert!(Ship::validate_fleet(&overlapping, &GameRules::default()).is_err());
+
+            let mut bent: Vec<Ship> = Ship::new_ships();
+            bent[1].location = vec![(5, 5), (6, 5), (7, 5), (7, 6)];
+            assert!(Ship::validate_fleet(&bent, &GameRules::default()).is_err());
+
+            let spaced: GameRules = GameRules {
+                no_touching: true,
+                ..GameRules::default()
+            };
+            assert!(Ship::validate_fleet(&Ship::new_ships(), &spaced).is_err());
+            let mut apart: Vec<Ship> = Ship::new_ships();
+            for (index, ship) in apart.iter_mut().enumerate() {
+                for (_, y_pos) in ship.location.iter_mut() {
+                    *y_pos = 2 * index;
+                }
+            }
+            assert!(Ship::validate_fleet(&apart, &spaced).is_ok());
+            // A shared corner counts as touching too
+            apart[4].location = vec![(3, 7), (4, 7)];
+            assert!(Ship::validate_fleet(&apart, &spaced).is_err());
+        }
+
+        #[test]
+        fn test_random_ships_are_valid() {
+            let mut rng: StdRng = StdRng::seed_from_u64(7);
+            for _ in 0..200 {
+                let ships: Vec<Ship> =
+                    Ship::random_ships(&mut rng, &GameRules::default(), false).unwrap();
+                assert!(Ship::validate_fleet(&ships, &GameRules::default()).is_ok());
+            }
+        }
+
+        #[test]
+        fn test_random_ships_no_touching() {
+            let mut rng: StdRng = StdRng::seed_from_u64(11);
+            for _ in 0..200 {
+                let ships: Vec<Ship> =
+                    Ship::random_ships(&mut rng, &GameRules::default(), true).unwrap();
+                assert!(Ship::validate_fleet(&ships, &GameRules::default()).is_ok());
+                for (index, ship) in ships.iter().enumerate() {
+                    let others: HashSet<(usize, usize)> = ships
+                        .iter()
+                        .enumerate()
+                        .filter(|(jndex, _)| *jndex != index)
+                        .flat_map(|(_, other)| other.location.clone())
+                        .collect();
+                    assert!(ship.location.iter().all(|(x_pos, y_pos)| {
+                        (x_pos.saturating_sub(1)..=x_pos + 1).all(|x_near| {
+                            (y_pos.saturating_sub(1)..=y_pos + 1)
+                                .all(|y_near| !others.contains(&(x_near, y_near)))
+                        })
+                    }));
+                }
+            }
+        }
+
+        #[test]
+        fn test_random_ships_are_reproducible() {
+            let first: Vec<Vec<Ship>> =
+                ShipSet::new_with_rng(4, &GameRules::default(), &mut StdRng::seed_from_u64(42))
+                    .unwrap();
+            let second: Vec<Vec<Ship>> =
+                ShipSet::new_with_rng(4, &GameRules::default(), &mut StdRng::seed_from_u64(42))
+                    .unwrap();
+            assert_eq!(
+                serde_json::to_string(&first).unwrap(),
+                serde_json::to_string(&second).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_random_ships_rectangular_board() {
+            let rules: GameRules = GameRules::new(12, 6, Ship::fleet(), 8);
+            let mut rng: StdRng = StdRng::seed_from_u64(3);
+            for _ in 0..200 {
+                let ships: Vec<Ship> = Ship::random_ships(&mut rng, &rules, false).unwrap();
+                assert!(Ship::validate_fleet(&ships, &rules).is_ok());
+            }
+        }
+
+        #[test]
+        fn test_random_ships_impossible_fleet() {
+            let rules: GameRules = GameRules::new(10, 10, vec![(ShipKind::Carrier, 11)], 8);
+            assert!(Ship::random_ships(&mut StdRng::seed_from_u64(0), &rules, false).is_err());
+
+            // Two rows only hold these ships if they may lie side by side
+            let mut rules: GameRules = GameRules::new(
+                5,
+                2,
+                vec![(ShipKind::Carrier, 5), (ShipKind::Battleship, 4)],
+                8,
+            );
+            assert!(ShipSet::new_with_rng(2, &rules, &mut StdRng::seed_from_u64(0)).is_ok());
+            rules.no_touching = true;
+            assert!(rules.validate(2).is_err());
+            assert!(ShipSet::new_with_rng(2, &rules, &mut StdRng::seed_from_u64(0)).is_err());
+        }
+
+        #[test]
+        fn test_fleet_presets() {
+            assert_eq!(Ship::fleet(), FleetPreset::Classic1990.fleet());
+            let lengths = |preset: FleetPreset| -> Vec<usize> {
+                let mut lengths: Vec<usize> = preset
+                    .fleet()
+                    .iter()
+                    .map(|(_, length)| *length)
+                    .collect::<Vec<usize>>();
+                lengths.sort_unstable();
+                lengths
+            };
+            assert_eq!(lengths(FleetPreset::Classic1990), vec![2, 3, 3, 4, 5]);
+            assert_eq!(lengths(FleetPreset::Classic1967), vec![2, 3, 3, 4, 5]);
+            assert_eq!(
+                lengths(FleetPreset::Russian),
+                vec![1, 1, 1, 1, 2, 2, 2, 3, 3, 4]
+            );
+            let mut rng: StdRng = StdRng::seed_from_u64(5);
+            for preset in FleetPreset::ALL {
+                let rules: GameRules = GameRules::with_preset(10, 10, preset, 8).unwrap();
+                for _ in 0..50 {
+                    let ships: Vec<Ship> = Ship::random_ships(&mut rng, &rules, true).unwrap();
+                    assert!(Ship::validate_fleet(&ships, &rules).is_ok());
+                }
+            }
+            assert!(GameRules::with_preset(3, 3, FleetPreset::Classic1990, 8).is_err());
+            assert!(GameRules::with_preset(4, 4, FleetPreset::Russian, 8).is_err());
+        }
+
+        #[test]
+        fn test_ship_kind_serialization() {
+            for kind in ShipKind::ALL {
+                let serialized: String = serde_json::to_string(&kind).unwrap();
+                assert_eq!(serialized, kind.id().to_string());
+                assert_eq!(serde_json::from_str::<ShipKind>(&serialized).unwrap(), kind);
+                assert_eq!(ShipKind::from_name(kind.display_name()), Some(kind));
+                assert_eq!(ShipKind::from_name(kind.asset_key()), Some(kind));
+            }
+            assert!(serde_json::from_str::<ShipKind>("42").is_err());
+            assert!(serde_json::from_str::<ShipKind>("\"Rowboat\"").is_err());
+        }
+
+        #[test]
+        fn test_legacy_ship_deserialization() {
+            let legacy: Ship =
+                serde_json::from_str(r#"{"name":"Patrol_Boat","location":[[0,4],[1,4]]}"#).unwrap();
+            assert_eq!(legacy.kind, ShipKind::PatrolBoat);
+            assert_eq!(
+                serde_json::to_string(&legacy).unwrap(),
+                r#"{"kind":4,"location":[[0,4],[1,4]]}"#
+            );
+            let fired_state: FiredState = serde_json::from_str(r#"{"Sunk":"Carrier"}"#).unwrap();
+            assert!(fired_state == FiredState::Sunk(ShipKind::Carrier));
+        }
+    }
+}
@@ -5,7 +5,6 @@ pub fn build(frontend_pages: Vec<&str>) {
     for page in frontend_pages.into_iter() {
         build_frontend(PathBuf::from(page));
     }
-    set_rocket_database();
 }
 
 fn build_frontend(frontend: PathBuf) {
@@ -19,7 +18,7 @@ fn build_frontend(frontend: PathBuf) {
         .expect(expect_string.as_str());
 }
 
-fn set_rocket_database() {
+pub fn set_rocket_database() {
     Command::new("docker")
         .args(&["start", "redis-stack-server"])
         .status()
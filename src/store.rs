@@ -0,0 +1,271 @@
+use crate::keys::PlayerKeys;
+use interact::link::GameList;
+use mechanics::event::GameEvent;
+use mechanics::game::Game;
+use rocket::async_trait;
+use rocket::tokio::task::yield_now;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub type SharedStore = Arc<dyn GameStore>;
+
+// Optimistic updates retry this many times before reporting the game as contended
+pub const UPDATE_ATTEMPTS: usize = 32;
+
+// Changes handed to the atomic updates, which may run them more than once
+pub type GameUpdate<'a> = dyn FnMut(&mut Game) -> bool + Send + 'a;
+pub type GameListUpdate<'a> = dyn FnMut(&mut GameList) + Send + 'a;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Counter {
+    Games,
+    Players,
+}
+
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Games => "game_count",
+                Self::Players => "player_id_count",
+            }
+        )
+    }
+}
+
+// Counters the event streams poll, each stream remembering the last value it saw
+// so every open client hears about a change rather than just the first to look
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Channel {
+    Links,
+    MainPage,
+    Game(u64),
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Links => write!(f, "links_version"),
+            Self::MainPage => write!(f, "main_page_version"),
+            Self::Game(game_id) => write!(f, "game_version_{game_id}"),
+        }
+    }
+}
+
+#[async_trait]
+pub trait GameStore: Send + Sync {
+    async fn get_game(&self, game_id: u64) -> Result<Game, String>;
+
+    async fn put_game(&self, game: &Game) -> Result<(), String>;
+
+    // Runs the change against the stored game and saves the game together with
+    // the events it raised, or saves nothing when the change returns false.
+    // Concurrent updates never overwrite each other, so the change may be run
+    // again on a fresh copy if another writer got in first
+    async fn update_game(&self, game_id: u64, update: &mut GameUpdate<'_>) -> Result<Game, String>;
+
+    async fn get_player_keys(&self, player_id: &str) -> Result<PlayerKeys, String>;
+
+    // Only stores the keys if the id is free, returning whether it was
+    async fn claim_player(&self, player_id: &str, keys: &PlayerKeys) -> Result<bool, String>;
+
+    // Fails rather than replace a player already using the new id
+    async fn rename_player(&self, from: &str, to: &str) -> Result<(), String>;
+
+    async fn list_games(&self) -> Result<GameList, String>;
+
+    async fn update_games(&self, update: &mut GameListUpdate<'_>) -> Result<(), String>;
+
+    // Returns the counter's new value, never handing the same one out twice
+    async fn increment(&self, counter: Counter) -> Result<u64, String>;
+
+    async fn append_events(&self, game_id: u64, events: &[GameEvent]) -> Result<(), String>;
+
+    async fn events(&self, game_id: u64) -> Result<Vec<GameEvent>, String>;
+
+    async fn notify(&self, channel: Channel) -> Result<(), String>;
+
+    // How often the channel has been notified, zero if never
+    async fn notification_count(&self, channel: Channel) -> Result<u64, String>;
+}
+
+// Player ids come from the counter, skipping any that a rename already took
+pub async fn register_new_player(store: &SharedStore, keys: &PlayerKeys) -> Result<String, String> {
+    loop {
+        let player_id: String = format!("player_{}", store.increment(Counter::Players).await? - 1);
+        if store.claim_player(&player_id, keys).await? {
+            return Ok(player_id);
+        }
+    }
+}
+
+#[derive(Default)]
+struct MemoryData {
+    // Games are kept serialized so they lose the same fields a Redis round trip does
+    games: HashMap<u64, String>,
+    // Bumped on every write of a game, playing the part of a Redis WATCH
+    versions: HashMap<u64, u64>,
+    players: HashMap<String, PlayerKeys>,
+    game_list: GameList,
+    counters: HashMap<Counter, u64>,
+    events: HashMap<u64, Vec<GameEvent>>,
+    notifications: HashMap<Channel, u64>,
+}
+
+// Keeps everything in the server's memory, for running without Redis and for tests
+#[derive(Default)]
+pub struct MemoryStore {
+    data: Mutex<MemoryData>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> MutexGuard<'_, MemoryData> {
+        self.data
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl GameStore for MemoryStore {
+    async fn get_game(&self, game_id: u64) -> Result<Game, String> {
+        let data: MutexGuard<'_, MemoryData> = self.data();
+        let game: &String = data
+            .games
+            .get(&game_id)
+            .ok_or_else(|| format!("store: get_game: game {game_id} does not exist"))?;
+        serde_json::from_str(game).map_err(|error| format!("store: get_game: {error}"))
+    }
+
+    async fn put_game(&self, game: &Game) -> Result<(), String> {
+        let mut data: MutexGuard<'_, MemoryData> = self.data();
+        data.games.insert(game.game_number, game.into());
+        *data.versions.entry(game.game_number).or_insert(0) += 1;
+        Ok(())
+    }
+
+    // The change runs without the lock, so like the Redis store this one only
+    // writes back if the game is still at the version the change started from
+    async fn update_game(&self, game_id: u64, update: &mut GameUpdate<'_>) -> Result<Game, String> {
+        for _ in 0..UPDATE_ATTEMPTS {
+            let (version, mut game): (u64, Game) = {
+                let data: MutexGuard<'_, MemoryData> = self.data();
+                match data.games.get(&game_id) {
+                    Some(game) => (
+                        data.versions.get(&game_id).copied().unwrap_or(0),
+                        serde_json::from_str(game)
+                            .map_err(|error| format!("store: update_game: {error}"))?,
+                    ),
+                    None => {
+                        return Err(format!("store: update_game: game {game_id} does not exist"))
+                    }
+                }
+            };
+            if !update(&mut game) {
+                return Ok(game);
+            }
+            // Lets other updates in between the read and the write, as a network hop would
+            yield_now().await;
+            let mut data: MutexGuard<'_, MemoryData> = self.data();
+            let current: &mut u64 = data.versions.entry(game_id).or_insert(0);
+            if *current != version {
+                continue;
+            }
+            *current += 1;
+            let events: Vec<GameEvent> = game.take_events();
+            data.games.insert(game_id, (&game).into());
+            data.events.entry(game_id).or_default().extend(events);
+            return Ok(game);
+        }
+        Err(format!(
+            "store: update_game: game {game_id} still contended after {UPDATE_ATTEMPTS} attempts"
+        ))
+    }
+
+    async fn get_player_keys(&self, player_id: &str) -> Result<PlayerKeys, String> {
+        self.data()
+            .players
+            .get(player_id)
+            .cloned()
+            .ok_or_else(|| format!("store: get_player_keys: {player_id} is not registered"))
+    }
+
+    async fn claim_player(&self, player_id: &str, keys: &PlayerKeys) -> Result<bool, String> {
+        match self.data().players.entry(player_id.to_string()) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                entry.insert(keys.clone());
+                Ok(true)
+            }
+        }
+    }
+
+    async fn rename_player(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut data: MutexGuard<'_, MemoryData> = self.data();
+        if data.players.contains_key(to) {
+            return Err(format!("store: rename_player: {to} is already taken"));
+        }
+        let keys: PlayerKeys = data
+            .players
+            .remove(from)
+            .ok_or_else(|| format!("store: rename_player: {from} is not registered"))?;
+        data.players.insert(to.to_string(), keys);
+        Ok(())
+    }
+
+    async fn list_games(&self) -> Result<GameList, String> {
+        Ok(self.data().game_list.clone())
+    }
+
+    async fn update_games(&self, update: &mut GameListUpdate<'_>) -> Result<(), String> {
+        update(&mut self.data().game_list);
+        Ok(())
+    }
+
+    async fn increment(&self, counter: Counter) -> Result<u64, String> {
+        let mut data: MutexGuard<'_, MemoryData> = self.data();
+        let count: &mut u64 = data.counters.entry(counter).or_insert(0);
+        *count += 1;
+        Ok(*count)
+    }
+
+    async fn append_events(&self, game_id: u64, events: &[GameEvent]) -> Result<(), String> {
+        self.data()
+            .events
+            .entry(game_id)
+            .or_default()
+            .extend(events.iter().cloned());
+        Ok(())
+    }
+
+    async fn events(&self, game_id: u64) -> Result<Vec<GameEvent>, String> {
+        Ok(self
+            .data()
+            .events
+            .get(&game_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn notify(&self, channel: Channel) -> Result<(), String> {
+        *self.data().notifications.entry(channel).or_insert(0) += 1;
+        Ok(())
+    }
+
+    async fn notification_count(&self, channel: Channel) -> Result<u64, String> {
+        Ok(self
+            .data()
+            .notifications
+            .get(&channel)
+            .copied()
+            .unwrap_or(0))
+    }
+}
@@ -0,0 +1,346 @@
+#[cfg(test)]
+mod tests {
+    mod store {
+        use battleship::database::RedisStore;
+        use battleship::keys::PlayerKeys;
+        use battleship::store::{
+            register_new_player, Channel, Counter, GameStore, MemoryStore, SharedStore,
+        };
+        use interact::link::{GameList, GameListEntry};
+        use mechanics::event::GameEvent;
+        use mechanics::game::{Game, GamePhase};
+        use mechanics::position::{FiredState, Target};
+        use mechanics::rules::GameRules;
+        use mechanics::ship::Ship;
+        use rocket::tokio::runtime::{Builder, Runtime};
+        use rocket::tokio::task::{block_in_place, JoinHandle};
+        use rocket_db_pools::deadpool_redis::{Config, Pool, PoolConfig, Runtime as PoolRuntime};
+        use std::future::Future;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Barrier};
+
+        const RACERS: usize = 8;
+
+        #[rocket::async_test]
+        async fn test_games_round_trip() {
+            let store: MemoryStore = MemoryStore::new();
+            assert!(store.get_game(1).await.is_err());
+            let mut game: Game = Game::new_with_seed(2, 1, GameRules::default(), 5).unwrap();
+            game.join("player_0".to_string()).unwrap();
+            store.put_game(&game).await.unwrap();
+            let stored: Game = store.get_game(1).await.unwrap();
+            assert_eq!(stored.player_tags, game.player_tags);
+            assert_eq!(stored.seed, 5);
+            // Like a Redis round trip, the pending events stay with the caller
+            assert!(stored.events().is_empty());
+
+            let events: Vec<GameEvent> = game.take_events();
+            store.append_events(1, &events[..1]).await.unwrap();
+            store.append_events(1, &events[1..]).await.unwrap();
+            assert_eq!(store.events(1).await.unwrap(), events);
+            assert!(store.events(2).await.unwrap().is_empty());
+        }
+
+        #[rocket::async_test]
+        async fn test_players_and_counters() {
+            let store: MemoryStore = MemoryStore::new();
+            assert_eq!(store.increment(Counter::Players).await.unwrap(), 1);
+            assert_eq!(store.increment(Counter::Players).await.unwrap(), 2);
+            assert_eq!(store.increment(Counter::Games).await.unwrap(), 1);
+
+            let keys: PlayerKeys = PlayerKeys::new();
+            assert!(store.claim_player("player_0", &keys).await.unwrap());
+            assert!(!store
+                .claim_player("player_0", &PlayerKeys::new())
+                .await
+                .unwrap());
+            store.rename_player("player_0", "Ada").await.unwrap();
+            assert!(store.get_player_keys("player_0").await.is_err());
+            assert_eq!(
+                store.get_player_keys("Ada").await.unwrap().decryption_key,
+                keys.decryption_key
+            );
+            assert!(store.rename_player("player_0", "Grace").await.is_err());
+            assert!(store.claim_player("Grace", &keys).await.unwrap());
+            assert!(store.rename_player("Grace", "Ada").await.is_err());
+            assert!(store.get_player_keys("Grace").await.is_ok());
+        }
+
+        #[rocket::async_test]
+        async fn test_game_list_and_notifications() {
+            let store: MemoryStore = MemoryStore::new();
+            assert!(store.list_games().await.unwrap().is_empty());
+            store
+                .update_games(&mut |games: &mut GameList| {
+                    games.push(GameListEntry::new(1, 2));
+                    games.push(GameListEntry::new(2, 4));
+                })
+                .await
+                .unwrap();
+            assert_eq!(store.list_games().await.unwrap().len(), 2);
+
+            assert_eq!(store.notification_count(Channel::Links).await.unwrap(), 0);
+            store.notify(Channel::Game(1)).await.unwrap();
+            // Reading the count leaves it for every other stream polling the game
+            assert_eq!(store.notification_count(Channel::Game(1)).await.unwrap(), 1);
+            assert_eq!(store.notification_count(Channel::Game(1)).await.unwrap(), 1);
+            store.notify(Channel::Game(1)).await.unwrap();
+            assert_eq!(store.notification_count(Channel::Game(1)).await.unwrap(), 2);
+            assert_eq!(store.notification_count(Channel::Game(2)).await.unwrap(), 0);
+        }
+
+        // Rocket's test runtime has a single thread, so racing stores get one
+        // with a worker for every racer
+        fn race<F: Future<Output = ()>>(test: impl Fn(SharedStore) -> F) {
+            let runtime: Runtime = Builder::new_multi_thread()
+                .worker_threads(RACERS + 1)
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(test(Arc::new(MemoryStore::new())));
+            // Redis only joins in when pointed at a scratch Redis Stack, as
+            // the tests write to the same keys the server uses
+            if let Ok(url) = std::env::var("REDIS_URL") {
+                let mut config: Config = Config::from_url(url);
+                // Every racer holds a connection while it waits on the others
+                config.pool = Some(PoolConfig::new(2 * RACERS));
+                let pool: Pool = config.create_pool(Some(PoolRuntime::Tokio1)).unwrap();
+                runtime.block_on(test(Arc::new(RedisStore::new(pool))));
+            }
+        }
+
+        // Every racer reads the game and then waits for the others before its
+        // change is written, so all but one write is refused and retried. The
+        // wait hands its worker's queue over, or a Redis reply woken onto a
+        // waiting worker would never reach the racer still reading.
+        // Returns how often the changes ran in total
+        async fn race_updates(
+            store: &SharedStore,
+            game_id: u64,
+            change: fn(usize, &mut Game) -> bool,
+        ) -> usize {
+            let barrier: Arc<Barrier> = Arc::new(Barrier::new(RACERS));
+            let attempts: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+            let racers: Vec<JoinHandle<()>> = (0..RACERS)
+                .map(|index: usize| {
+                    let store: SharedStore = store.clone();
+                    let barrier: Arc<Barrier> = barrier.clone();
+                    let attempts: Arc<AtomicUsize> = attempts.clone();
+                    rocket::tokio::spawn(async move {
+                        let mut waited: bool = false;
+                        store
+                            .update_game(game_id, &mut |game: &mut Game| -> bool {
+                                attempts.fetch_add(1, Ordering::SeqCst);
+                                if !waited {
+                                    waited = true;
+                                    block_in_place(|| barrier.wait());
+                                }
+                                change(index, game)
+                            })
+                            .await
+                            .unwrap();
+                    })
+                })
+                .collect::<Vec<JoinHandle<()>>>();
+            for racer in racers {
+                racer.await.unwrap();
+            }
+            attempts.load(Ordering::SeqCst)
+        }
+
+        #[test]
+        fn test_concurrent_notifications() {
+            race(|store: SharedStore| async move {
+                let game_id: u64 = store.increment(Counter::Games).await.unwrap();
+                let notifies: Vec<JoinHandle<()>> = (0..RACERS)
+                    .map(|_| {
+                        let store: SharedStore = store.clone();
+                        rocket::tokio::spawn(async move {
+                            store.notify(Channel::Game(game_id)).await.unwrap();
+                        })
+                    })
+                    .collect::<Vec<JoinHandle<()>>>();
+                for notify in notifies {
+                    notify.await.unwrap();
+                }
+                assert_eq!(
+                    store
+                        .notification_count(Channel::Game(game_id))
+                        .await
+                        .unwrap(),
+                    RACERS as u64
+                );
+            });
+        }
+
+        #[test]
+        fn test_updates_do_not_overwrite_each_other() {
+            race(|store: SharedStore| async move {
+                let game_id: u64 = store.increment(Counter::Games).await.unwrap();
+                let mut game: Game = Game::new(RACERS, game_id, GameRules::default()).unwrap();
+                store.put_game(&game).await.unwrap();
+                store
+                    .append_events(game_id, &game.take_events())
+                    .await
+                    .unwrap();
+
+                let joins: usize =
+                    race_updates(&store, game_id, |index: usize, game: &mut Game| {
+                        game.join(format!("player_{index}")).is_ok()
+                    })
+                    .await;
+                let placements: usize =
+                    race_updates(&store, game_id, |index: usize, game: &mut Game| {
+                        game.place_fleet(index, Ship::new_ships()).is_ok()
+                    })
+                    .await;
+                let game: Game = store
+                    .update_game(game_id, &mut |game: &mut Game| -> bool {
+                        game.start("challenge".to_string()).is_ok()
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(game.phase, GamePhase::InProgress { round: 1 });
+                let mut player_tags: Vec<String> = game.player_tags.clone();
+                player_tags.sort();
+                assert_eq!(
+                    player_tags,
+                    (0..RACERS)
+                        .map(|index: usize| format!("player_{index}"))
+                        .collect::<Vec<String>>()
+                );
+
+                // Every player fires into the open water of the next one at once
+                let shots: usize = race_updates(&store, game_id, |from: usize, game: &mut Game| {
+                    game.fire(from, (from + 1) % RACERS, from, 9).is_ok()
+                })
+                .await;
+                for attempts in [joins, placements, shots] {
+                    assert!(2 * RACERS - 1 <= attempts);
+                }
+                let game: Game = store.get_game(game_id).await.unwrap();
+                assert!(game.round_complete());
+                for from in 0..RACERS {
+                    let target: Target = Target::new((from + 1) % RACERS, from, 9);
+                    assert_eq!(
+                        game.boards.positions[target.lon][target.lat].fired_state[target.to],
+                        FiredState::Miss
+                    );
+                }
+                let events: Vec<GameEvent> = store.events(game_id).await.unwrap();
+                assert_eq!(
+                    events
+                        .iter()
+                        .filter(|event: &&GameEvent| matches!(event, GameEvent::ShotFired { .. }))
+                        .count(),
+                    RACERS
+                );
+                assert!(Game::replay(&events).is_ok());
+                assert_eq!(game.phase, GamePhase::InProgress { round: 1 });
+            });
+        }
+
+        #[test]
+        fn test_concurrent_registration() {
+            race(|store: SharedStore| async move {
+                // A Redis store keeps its counter between runs, so every id
+                // here is made unique to this one
+                let run: u64 = store.increment(Counter::Players).await.unwrap();
+                // Someone already renamed themselves onto an id the counter has yet to reach
+                let ada: String = format!("ada_{run}");
+                store.claim_player(&ada, &PlayerKeys::new()).await.unwrap();
+                store
+                    .rename_player(&ada, &format!("player_{}", run + 3))
+                    .await
+                    .unwrap();
+
+                let registrations: Vec<JoinHandle<(String, PlayerKeys)>> = (0..4 * RACERS)
+                    .map(|_| {
+                        let store: SharedStore = store.clone();
+                        rocket::tokio::spawn(async move {
+                            let keys: PlayerKeys = PlayerKeys::new();
+                            let player_id: String =
+                                register_new_player(&store, &keys).await.unwrap();
+                            (player_id, keys)
+                        })
+                    })
+                    .collect::<Vec<JoinHandle<(String, PlayerKeys)>>>();
+                let renames: Vec<JoinHandle<bool>> = (0..RACERS as u64)
+                    .map(|index: u64| {
+                        let store: SharedStore = store.clone();
+                        rocket::tokio::spawn(async move {
+                            let name: String = format!("name_{run}_{index}");
+                            store.claim_player(&name, &PlayerKeys::new()).await.unwrap();
+                            store
+                                .rename_player(&name, &format!("player_{}", run + 10 + index))
+                                .await
+                                .is_ok()
+                        })
+                    })
+                    .collect::<Vec<JoinHandle<bool>>>();
+                // Racers going for the same id, once by claiming and once by renaming
+                let contests: Vec<JoinHandle<(bool, bool, PlayerKeys)>> = (0..RACERS)
+                    .map(|index: usize| {
+                        let store: SharedStore = store.clone();
+                        rocket::tokio::spawn(async move {
+                            let keys: PlayerKeys = PlayerKeys::new();
+                            let name: String = format!("racer_{run}_{index}");
+                            store.claim_player(&name, &keys).await.unwrap();
+                            let claimed: bool = store
+                                .claim_player(&format!("claimed_{run}"), &keys)
+                                .await
+                                .unwrap();
+                            let renamed: bool = store
+                                .rename_player(&name, &format!("renamed_{run}"))
+                                .await
+                                .is_ok();
+                            (claimed, renamed, keys)
+                        })
+                    })
+                    .collect::<Vec<JoinHandle<(bool, bool, PlayerKeys)>>>();
+
+                let mut player_ids: Vec<String> = Vec::new();
+                for registration in registrations {
+                    let (player_id, keys) = registration.await.unwrap();
+                    // Nobody else's keys ended up under this id
+                    assert_eq!(
+                        store
+                            .get_player_keys(&player_id)
+                            .await
+                            .unwrap()
+                            .decryption_key,
+                        keys.decryption_key
+                    );
+                    player_ids.push(player_id);
+                }
+                let mut renamed: Vec<String> = vec![format!("player_{}", run + 3)];
+                for (index, rename) in renames.into_iter().enumerate() {
+                    if rename.await.unwrap() {
+                        renamed.push(format!("player_{}", run + 10 + index as u64));
+                    }
+                }
+                player_ids.sort();
+                player_ids.dedup();
+                assert_eq!(player_ids.len(), 4 * RACERS);
+                assert!(player_ids
+                    .iter()
+                    .all(|player_id: &String| !renamed.contains(player_id)));
+
+                let (mut claims, mut renames): (usize, usize) = (0, 0);
+                for contest in contests {
+                    let (claimed, renamed, keys) = contest.await.unwrap();
+                    for (won, player_id) in [(claimed, "claimed"), (renamed, "renamed")] {
+                        let stored: PlayerKeys = store
+                            .get_player_keys(&format!("{player_id}_{run}"))
+                            .await
+                            .unwrap();
+                        assert_eq!(won, stored.decryption_key == keys.decryption_key);
+                    }
+                    claims += claimed as usize;
+                    renames += renamed as usize;
+                }
+                assert_eq!((claims, renames), (1, 1));
+            });
+        }
+    }
+}